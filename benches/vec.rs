@@ -0,0 +1,166 @@
+// Compares this crate's Vec against std's on the basic operations. Run with
+// `cargo +nightly bench`; criterion reports regressions against the previous run.
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod vec {
+    use criterion::{criterion_group, BatchSize, BenchmarkId, Criterion};
+    use std::hint::black_box;
+
+    const N: usize = 10_000;
+
+    fn filled() -> rust_vec::Vec<u64> {
+        (0..N as u64).collect()
+    }
+
+    fn filled_std() -> Vec<u64> {
+        (0..N as u64).collect()
+    }
+
+    fn push(c: &mut Criterion) {
+        let mut g = c.benchmark_group("push");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter(|| {
+                let mut v = rust_vec::Vec::new();
+                for i in 0..N as u64 {
+                    v.push(black_box(i));
+                }
+                v
+            })
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                for i in 0..N as u64 {
+                    v.push(black_box(i));
+                }
+                v
+            })
+        });
+        g.finish();
+    }
+
+    fn extend(c: &mut Criterion) {
+        let mut g = c.benchmark_group("extend");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter(|| {
+                let mut v = rust_vec::Vec::new();
+                v.extend(black_box(0..N as u64));
+                v
+            })
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                v.extend(black_box(0..N as u64));
+                v
+            })
+        });
+        g.finish();
+    }
+
+    fn insert_front(c: &mut Criterion) {
+        const M: usize = 1_000;
+        let mut g = c.benchmark_group("insert_front");
+        g.bench_function(BenchmarkId::new("rust_vec", M), |b| {
+            b.iter(|| {
+                let mut v = rust_vec::Vec::new();
+                for i in 0..M as u64 {
+                    v.insert(0, black_box(i));
+                }
+                v
+            })
+        });
+        g.bench_function(BenchmarkId::new("std", M), |b| {
+            b.iter(|| {
+                let mut v = Vec::new();
+                for i in 0..M as u64 {
+                    v.insert(0, black_box(i));
+                }
+                v
+            })
+        });
+        g.finish();
+    }
+
+    fn remove(c: &mut Criterion) {
+        let mut g = c.benchmark_group("remove_middle");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter_batched_ref(
+                filled,
+                |v| {
+                    while v.len() > N / 2 {
+                        black_box(v.remove(v.len() / 2));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter_batched_ref(
+                filled_std,
+                |v| {
+                    while v.len() > N / 2 {
+                        black_box(v.remove(v.len() / 2));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        g.finish();
+    }
+
+    fn iterate(c: &mut Criterion) {
+        let (v, s) = (filled(), filled_std());
+        let mut g = c.benchmark_group("iter_sum");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter(|| black_box(&v).iter().sum::<u64>())
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter(|| black_box(&s).iter().sum::<u64>())
+        });
+        g.finish();
+    }
+
+    fn drain(c: &mut Criterion) {
+        let mut g = c.benchmark_group("drain");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter_batched_ref(filled, |v| v.drain().sum::<u64>(), BatchSize::SmallInput)
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter_batched_ref(
+                filled_std,
+                |v| v.drain(..).sum::<u64>(),
+                BatchSize::SmallInput,
+            )
+        });
+        g.finish();
+    }
+
+    // Elements without drop glue: both should be a length store, independent of `N`.
+    fn clear(c: &mut Criterion) {
+        let mut g = c.benchmark_group("clear");
+        g.bench_function(BenchmarkId::new("rust_vec", N), |b| {
+            b.iter_batched_ref(filled, |v| v.clear(), BatchSize::SmallInput)
+        });
+        g.bench_function(BenchmarkId::new("std", N), |b| {
+            b.iter_batched_ref(filled_std, |v| v.clear(), BatchSize::SmallInput)
+        });
+        g.finish();
+    }
+
+    criterion_group!(
+        benches,
+        push,
+        extend,
+        insert_front,
+        remove,
+        iterate,
+        drain,
+        clear
+    );
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+criterion::criterion_main!(vec::benches);
+
+#[cfg(feature = "no_global_oom_handling")]
+fn main() {}
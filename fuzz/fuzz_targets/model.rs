@@ -0,0 +1,12 @@
+// Differential fuzzing against std's Vec: `cargo +nightly fuzz run model`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_vec::model::{run, LeakCheckAlloc, Op};
+
+#[global_allocator]
+static ALLOC: LeakCheckAlloc = LeakCheckAlloc;
+
+fuzz_target!(|data: &[u8]| {
+    run(&Op::decode(data));
+});
@@ -0,0 +1,79 @@
+use crate::Vec;
+use rkyv::rancor::Fallible;
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::{Archive, Deserialize, Place, Serialize};
+
+// Archives to rkyv's own `ArchivedVec`, the same layout as std's `Vec`, so archived data can be
+// read in place (e.g. from a memory map) with `rkyv::access` and either side can produce it.
+impl<T: Archive> Archive for Vec<T> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_slice(self, resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for Vec<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(self, serializer)
+    }
+}
+
+impl<T, D> Deserialize<Vec<T>, D> for ArchivedVec<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<Vec<T>, D::Error> {
+        let mut v = Vec::with_capacity(self.len());
+        for elem in self.iter() {
+            v.push(elem.deserialize(deserializer)?);
+        }
+        Ok(v)
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for ArchivedVec<T> {
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self.as_slice() == &other[..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rkyv::rancor::Error;
+    use rkyv::Archived;
+
+    #[test]
+    fn roundtrip() {
+        let mut v: Vec<Vec<u32>> = Vec::new();
+        for n in 0..4 {
+            v.push((0..n).collect());
+        }
+        let bytes = rkyv::to_bytes::<Error>(&v).unwrap();
+        let archived = unsafe { rkyv::access_unchecked::<Archived<Vec<Vec<u32>>>>(&bytes) };
+        assert_eq!(archived.len(), 4);
+        assert!(archived[3] == (0..3).collect::<Vec<u32>>());
+        let back: Vec<Vec<u32>> = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(back, v);
+    }
+
+    // Same archived layout as std's `Vec`, so the two are interchangeable on disk.
+    #[test]
+    fn std_compatible() {
+        let mut ours = Vec::new();
+        ours.extend_from_slice(&[1u64, 2, 3]);
+        let bytes = rkyv::to_bytes::<Error>(&ours).unwrap();
+        let theirs =
+            unsafe { rkyv::from_bytes_unchecked::<alloc::vec::Vec<u64>, Error>(&bytes) }.unwrap();
+        assert_eq!(theirs, [1, 2, 3]);
+    }
+}
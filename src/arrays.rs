@@ -0,0 +1,67 @@
+use crate::{IntoIter, Vec};
+use alloc::alloc::Global;
+use core::alloc::Allocator;
+
+// Consumes the vector as `[T; N]` arrays, moving each one out with a single read. Elements that
+// don't fill a last array are left in `remainder` and dropped with the iterator.
+pub struct IntoArrays<T, const N: usize, A: Allocator = Global> {
+    iter: IntoIter<T, A>,
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn into_arrays<const N: usize>(self) -> IntoArrays<T, N, A> {
+        assert!(N != 0, "array length must be non-zero");
+        IntoArrays {
+            iter: self.into_iter(),
+        }
+    }
+}
+
+impl<T, const N: usize, A: Allocator> IntoArrays<T, N, A> {
+    pub fn remainder(&self) -> &[T] {
+        let rest = self.iter.as_slice();
+        &rest[rest.len() - rest.len() % N..]
+    }
+}
+
+impl<T, const N: usize, A: Allocator> Iterator for IntoArrays<T, N, A> {
+    type Item = [T; N];
+
+    fn next(&mut self) -> Option<[T; N]> {
+        self.iter.iter.next_array()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.iter.as_slice().len() / N;
+        (n, Some(n))
+    }
+}
+
+impl<T, const N: usize, A: Allocator> ExactSizeIterator for IntoArrays<T, N, A> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+    use alloc::boxed::Box;
+
+    #[test]
+    fn into_arrays() {
+        let bytes: Vec<u8> = (0..10).collect();
+        let mut pixels = bytes.into_arrays::<4>();
+        assert_eq!(pixels.len(), 2);
+        assert_eq!(pixels.remainder(), &[8, 9]);
+        assert_eq!(pixels.next(), Some([0, 1, 2, 3]));
+        assert_eq!(pixels.next(), Some([4, 5, 6, 7]));
+        assert_eq!(pixels.next(), None);
+        assert_eq!(pixels.remainder(), &[8, 9]);
+
+        // Owned elements are moved out, and the unread ones dropped with the iterator
+        let boxes = Vec::from_fn(5, Box::new);
+        let mut it = boxes.into_arrays::<2>();
+        let [a, b] = it.next().unwrap();
+        assert_eq!((*a, *b), (0, 1));
+        drop(it);
+
+        assert_eq!(Vec::from_fn(4, |_| ()).into_arrays::<3>().count(), 1);
+    }
+}
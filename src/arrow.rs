@@ -0,0 +1,155 @@
+use crate::{NullableVec, Vec};
+use alloc::alloc::Global;
+use alloc::sync::Arc;
+use arrow_array::{ArrowPrimitiveType, PrimitiveArray};
+use arrow_buffer::{ArrowNativeType, BooleanBuffer, Buffer, NullBuffer, ScalarBuffer};
+use core::alloc::{AllocError, Allocator, Layout};
+use core::panic::AssertUnwindSafe;
+use core::ptr::NonNull;
+
+// The alignment Arrow's own buffers use, so that kernels can assume whole cache lines and SIMD
+// loads never straddle one.
+pub const ARROW_ALIGN: usize = 64;
+
+// The global allocator with every buffer aligned to `ARROW_ALIGN`. Build vectors meant for Arrow
+// with `Vec::new_in(ArrowAlloc)` and they are handed over without a copy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArrowAlloc;
+
+fn aligned(layout: Layout) -> Layout {
+    layout.align_to(ARROW_ALIGN).unwrap()
+}
+
+unsafe impl Allocator for ArrowAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Global.allocate(aligned(layout))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, aligned(layout))
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Global.grow(ptr, aligned(old_layout), aligned(new_layout))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Global.shrink(ptr, aligned(old_layout), aligned(new_layout))
+    }
+}
+
+// Hands an `ARROW_ALIGN`-aligned allocation (always the case with `ArrowAlloc`) to Arrow without
+// copying; the vector is kept alive as the buffer's owner and freed when the last Arrow reference
+// goes away (it is never accessed through the owner, so unwind safety is moot). Anything else is
+// copied into a buffer Arrow allocates itself.
+impl<T: ArrowNativeType, A: Allocator + Send + Sync + 'static> From<Vec<T, A>> for Buffer {
+    fn from(v: Vec<T, A>) -> Self {
+        if !(v.as_ptr() as usize).is_multiple_of(ARROW_ALIGN) {
+            return Buffer::from_slice_ref(&v[..]);
+        }
+        let ptr = NonNull::new(v.as_ptr() as *mut u8).unwrap();
+        let len = core::mem::size_of_val(&v[..]);
+        unsafe { Buffer::from_custom_allocation(ptr, len, Arc::new(AssertUnwindSafe(v))) }
+    }
+}
+
+impl<T: ArrowNativeType, A: Allocator + Send + Sync + 'static> From<Vec<T, A>> for ScalarBuffer<T> {
+    fn from(v: Vec<T, A>) -> Self {
+        let len = v.len();
+        ScalarBuffer::new(Buffer::from(v), 0, len)
+    }
+}
+
+// Arrow owns its allocations, so going the other way always copies.
+impl<T: ArrowNativeType> From<&ScalarBuffer<T>> for Vec<T> {
+    fn from(buf: &ScalarBuffer<T>) -> Self {
+        let mut v = Vec::with_capacity(buf.len());
+        v.extend(buf.iter().copied());
+        v
+    }
+}
+
+impl<P: ArrowPrimitiveType> From<NullableVec<P::Native>> for PrimitiveArray<P> {
+    fn from(v: NullableVec<P::Native>) -> Self {
+        let len = v.len();
+        let (values, validity, nulls) = v.into_parts();
+        // Null slots are zeroed, so every slot is a valid native value.
+        let values: Vec<P::Native> = unsafe { values.cast() };
+        let nulls =
+            (nulls > 0).then(|| NullBuffer::new(BooleanBuffer::new(validity.into(), 0, len)));
+        PrimitiveArray::new(values.into(), nulls)
+    }
+}
+
+impl<P: ArrowPrimitiveType> From<&PrimitiveArray<P>> for NullableVec<P::Native> {
+    fn from(array: &PrimitiveArray<P>) -> Self {
+        let mut v = NullableVec::with_capacity(array.len());
+        v.extend(array.iter());
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::types::Int32Type;
+    use arrow_array::Array;
+
+    #[test]
+    fn vec_to_buffer_is_zero_copy() {
+        let mut v = Vec::new_in(ArrowAlloc);
+        for i in 0..10u64 {
+            v.push(i);
+            assert_eq!(v.as_ptr() as usize % ARROW_ALIGN, 0);
+        }
+        v.shrink_to_fit();
+        let ptr = v.as_ptr();
+        let buf = ScalarBuffer::from(v);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(&buf[..], &(0..10).collect::<std::vec::Vec<u64>>()[..]);
+        assert_eq!(&Vec::from(&buf)[..], &buf[..]);
+    }
+
+    #[test]
+    fn buffers_are_aligned() {
+        // Whichever path each one takes, Arrow gets 64-byte aligned data
+        for len in 0..20 {
+            let v: Vec<u8> = (0..len).collect();
+            let buf = Buffer::from(v);
+            assert_eq!(buf.as_ptr() as usize % ARROW_ALIGN, 0);
+            assert_eq!(buf.as_slice(), &(0..len).collect::<std::vec::Vec<u8>>()[..]);
+        }
+        let v: NullableVec<i32> = (0..5).map(Some).collect();
+        let array = PrimitiveArray::<Int32Type>::from(v);
+        assert_eq!(array.values().as_ptr() as usize % ARROW_ALIGN, 0);
+    }
+
+    #[test]
+    fn nullable_roundtrip() {
+        let v: NullableVec<i32> = (0..10).map(|i| (i % 4 != 0).then_some(i)).collect();
+        let array = PrimitiveArray::<Int32Type>::from(v);
+        assert_eq!(array.len(), 10);
+        assert_eq!(array.null_count(), 3);
+        assert!(array.is_null(4));
+        assert_eq!(array.value(5), 5);
+
+        let back = NullableVec::from(&array);
+        assert!(back
+            .iter()
+            .map(|x| x.copied())
+            .eq((0..10).map(|i| (i % 4 != 0).then_some(i))));
+
+        let dense: NullableVec<i32> = (0..3).map(Some).collect();
+        assert!(PrimitiveArray::<Int32Type>::from(dense).nulls().is_none());
+    }
+}
@@ -0,0 +1,79 @@
+// AddressSanitizer annotations for spare capacity. Under `-Zsanitizer=address` the elements
+// between `len` and `cap` of a `Vec` are poisoned whenever control is outside this crate, so a
+// read or write past the end through a raw pointer is reported even though it stays inside the
+// allocation. Code that fills spare capacity by hand has to go through `spare_capacity_mut`
+// (or `into_raw_parts`), which unpoisons it. Without ASAN these compile to nothing.
+//
+// Reallocation unpoisons the whole buffer first: allocators that copy or hand the block to
+// someone else must not trip over our marks.
+#[cfg(sanitize = "address")]
+use core::mem;
+
+#[cfg(sanitize = "address")]
+extern "C" {
+    fn __asan_poison_memory_region(addr: *const u8, size: usize);
+    fn __asan_unpoison_memory_region(addr: *const u8, size: usize);
+}
+
+#[inline]
+pub(crate) fn poison<T>(start: *const T, count: usize) {
+    #[cfg(sanitize = "address")]
+    if mem::size_of::<T>() != 0 && count != 0 {
+        unsafe { __asan_poison_memory_region(start.cast(), count * mem::size_of::<T>()) }
+    }
+    #[cfg(not(sanitize = "address"))]
+    let _ = (start, count);
+}
+
+#[inline]
+pub(crate) fn unpoison<T>(start: *const T, count: usize) {
+    #[cfg(sanitize = "address")]
+    if mem::size_of::<T>() != 0 && count != 0 {
+        unsafe { __asan_unpoison_memory_region(start.cast(), count * mem::size_of::<T>()) }
+    }
+    #[cfg(not(sanitize = "address"))]
+    let _ = (start, count);
+}
+
+#[cfg(all(test, sanitize = "address", not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+
+    extern "C" {
+        fn __asan_address_is_poisoned(addr: *const u8) -> i32;
+    }
+
+    fn poisoned<T>(v: &Vec<T>, i: usize) -> bool {
+        unsafe { __asan_address_is_poisoned(v.as_ptr().add(i).cast()) != 0 }
+    }
+
+    #[test]
+    fn spare_is_poisoned() {
+        let mut v: Vec<u64> = Vec::with_capacity(4);
+        assert!(poisoned(&v, 0));
+        v.push(1);
+        v.push(2);
+        assert!(!poisoned(&v, 1) && poisoned(&v, 2));
+        for i in 0..10 {
+            v.push(i);
+        }
+        assert!(!poisoned(&v, 11) && poisoned(&v, 12));
+        v.truncate(3);
+        assert!(!poisoned(&v, 2) && poisoned(&v, 3));
+        v.pop();
+        assert!(poisoned(&v, 2));
+        v.extend_from_slice(&[7, 8, 9]);
+        assert!(!poisoned(&v, 4) && poisoned(&v, 5));
+    }
+
+    #[test]
+    fn spare_capacity_mut_unpoisons() {
+        let mut v: Vec<u8> = Vec::with_capacity(8);
+        let spare = v.spare_capacity_mut();
+        spare[0].write(1);
+        spare[1].write(2);
+        unsafe { v.set_len(2) };
+        assert_eq!(&v[..], &[1, 2]);
+        assert!(poisoned(&v, 2));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::Vec;
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::alloc::Allocator;
+use core::mem;
+
+// Borsh encodes a vector as a `u32` length followed by the elements, same as std's `Vec`.
+// Zero-sized elements are refused both ways, as borsh does for its own collections.
+
+// Allocation made up front for a decoded length, before any element has been read. A forged
+// length can't reserve more than this; real data grows the vector as elements arrive.
+const PREALLOC_BYTES: usize = 4096;
+
+// A vector that refuses to deserialize more than `MAX` elements, so an oversized length prefix
+// is rejected before anything is allocated. Use it as a field type in derived structs; the
+// encoding is the same as a plain `Vec<T>`.
+#[derive(Debug, Default, PartialEq)]
+pub struct BoundedLen<T, const MAX: usize>(pub Vec<T>);
+
+fn check_zst<T>() -> Result<()> {
+    if mem::size_of::<T>() == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "vectors of zero-sized types are not supported",
+        ));
+    }
+    Ok(())
+}
+
+impl<T: BorshSerialize, A: Allocator> BorshSerialize for Vec<T, A> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        check_zst::<T>()?;
+        (**self).serialize(writer)
+    }
+}
+
+impl<T: BorshDeserialize> Vec<T> {
+    // Like `deserialize_reader`, but fails on a length prefix above `max_len`.
+    pub fn deserialize_with_max_len<R: Read>(reader: &mut R, max_len: usize) -> Result<Self> {
+        check_zst::<T>()?;
+        let len = u32::deserialize_reader(reader)? as usize;
+        if len > max_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "vector length exceeds the maximum",
+            ));
+        }
+        let mut v = Vec::with_capacity(len.min(PREALLOC_BYTES / mem::size_of::<T>()));
+        for _ in 0..len {
+            v.push(T::deserialize_reader(reader)?);
+        }
+        Ok(v)
+    }
+}
+
+impl<T: BorshDeserialize> BorshDeserialize for Vec<T> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Self::deserialize_with_max_len(reader, u32::MAX as usize)
+    }
+}
+
+impl<T: BorshSerialize, const MAX: usize> BorshSerialize for BoundedLen<T, MAX> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.serialize(writer)
+    }
+}
+
+impl<T: BorshDeserialize, const MAX: usize> BorshDeserialize for BoundedLen<T, MAX> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        Vec::deserialize_with_max_len(reader, MAX).map(BoundedLen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut v: Vec<Vec<u16>> = Vec::new();
+        for n in 0..3 {
+            v.push((0..n).collect());
+        }
+        let bytes = borsh::to_vec(&v).unwrap();
+        // Same encoding as std's `Vec`
+        let std_v: alloc::vec::Vec<alloc::vec::Vec<u16>> =
+            v.iter().map(|x| x.iter().copied().collect()).collect();
+        assert_eq!(bytes, borsh::to_vec(&std_v).unwrap());
+        let back: Vec<Vec<u16>> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(back, v);
+    }
+
+    #[test]
+    fn rejects_bad_lengths() {
+        // Claims four billion elements but carries two
+        let forged = [0xff, 0xff, 0xff, 0xf0, 1, 2];
+        assert!(borsh::from_slice::<Vec<u8>>(&forged).is_err());
+
+        let mut v = Vec::new();
+        v.extend_from_slice(&[1u8, 2, 3]);
+        let bytes = borsh::to_vec(&v).unwrap();
+        let err = Vec::<u8>::deserialize_with_max_len(&mut &bytes[..], 2).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(borsh::from_slice::<BoundedLen<u8, 2>>(&bytes).is_err());
+        let bounded = borsh::from_slice::<BoundedLen<u8, 3>>(&bytes).unwrap();
+        assert_eq!(bounded.0, v);
+        assert_eq!(borsh::to_vec(&bounded).unwrap(), bytes);
+        assert_eq!(borsh::from_slice::<Vec<u8>>(&bytes).unwrap(), v);
+
+        assert!(borsh::to_vec(&Vec::<()>::new()).is_err());
+    }
+}
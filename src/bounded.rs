@@ -0,0 +1,119 @@
+use crate::{MemoryReport, Vec};
+use core::ops::{Deref, DerefMut};
+
+// A vector with a hard length limit fixed at construction. The buffer grows by doubling like a
+// normal vector, but each step is clamped to `max_len`, so capacity never exceeds the limit.
+pub struct BoundedVec<T> {
+    vec: Vec<T>,
+    max_len: usize,
+}
+
+impl<T> BoundedVec<T> {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            vec: Vec::new(),
+            max_len,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.vec.len == self.max_len
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.max_len - self.vec.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    // Hands `elem` back instead of growing past `max_len`.
+    pub fn push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+        let (len, cap) = (self.vec.len, self.vec.buf.cap);
+        if len == cap {
+            let new_cap = cap.saturating_mul(2).max(4).min(self.max_len);
+            self.vec.buf.reserve(len, new_cap - len, false);
+            self.vec.poison_spare();
+        }
+        self.vec.push(elem);
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.vec.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+impl<T> Deref for BoundedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> DerefMut for BoundedVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+}
+
+impl<T> MemoryReport for BoundedVec<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.vec.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.vec.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_length_and_capacity() {
+        let mut v = BoundedVec::new(10);
+        for i in 0..10 {
+            assert_eq!(v.push(i), Ok(()));
+            assert!(v.capacity() <= 10);
+        }
+        assert!(v.is_full());
+        assert_eq!(v.push(10), Err(10));
+        assert_eq!(v.capacity(), 10);
+        assert_eq!(v.pop(), Some(9));
+        assert_eq!(v.remaining(), 1);
+        v[0] = 100;
+        assert_eq!(&v[..3], &[100, 1, 2]);
+
+        let mut empty = BoundedVec::new(0);
+        assert_eq!(empty.push(()), Err(()));
+        let mut tiny = BoundedVec::new(3);
+        for i in 0..3 {
+            tiny.push(i).unwrap();
+        }
+        assert_eq!(tiny.capacity(), 3);
+        assert_eq!(tiny.into_inner().len(), 3);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::Vec;
+use bytes::buf::UninitSlice;
+use bytes::{BufMut, Bytes, BytesMut};
+
+unsafe impl BufMut for Vec<u8> {
+    fn remaining_mut(&self) -> usize {
+        isize::MAX as usize - self.len()
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.capacity() - self.len(),
+            "advance_mut out of bounds"
+        );
+        unsafe { self.set_len(self.len() + cnt) };
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        if self.capacity() == self.len() {
+            self.reserve(64);
+        }
+        UninitSlice::uninit(self.spare_capacity_mut())
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.reserve(src.len());
+        let len = self.len();
+        unsafe {
+            let dst = self.spare_capacity_mut().as_mut_ptr() as *mut u8;
+            core::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+            self.set_len(len + src.len());
+        }
+    }
+}
+
+// The conversions below go through std's Vec, which shares our allocation layout, so the
+// buffer itself is handed over rather than copied.
+impl From<Vec<u8>> for Bytes {
+    fn from(v: Vec<u8>) -> Self {
+        Bytes::from(alloc::vec::Vec::from(v))
+    }
+}
+
+impl From<Vec<u8>> for BytesMut {
+    fn from(v: Vec<u8>) -> Self {
+        BytesMut::from(Bytes::from(v))
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(b: Bytes) -> Self {
+        Vec::from(alloc::vec::Vec::from(b))
+    }
+}
+
+impl From<BytesMut> for Vec<u8> {
+    fn from(b: BytesMut) -> Self {
+        Vec::from(alloc::vec::Vec::from(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buf_mut() {
+        let mut v = Vec::new();
+        v.put_u8(1);
+        v.put_u32(0x0203_0405);
+        v.put_slice(b"abc");
+        assert_eq!(&v[..], &[1, 2, 3, 4, 5, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn bytes_conversion() {
+        let mut v = Vec::new();
+        v.put_slice(b"hello");
+        let ptr = v.as_ptr();
+        let b = Bytes::from(v);
+        assert_eq!(&b[..], b"hello");
+        assert_eq!(b.as_ptr(), ptr);
+        let mut m = BytesMut::from(Vec::from(b));
+        m.extend_from_slice(b" world");
+        let v = Vec::from(m);
+        assert_eq!(&v[..], b"hello world");
+    }
+}
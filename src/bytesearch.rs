@@ -0,0 +1,50 @@
+use crate::Vec;
+use core::alloc::Allocator;
+
+impl<A: Allocator> Vec<u8, A> {
+    pub fn find_byte(&self, byte: u8) -> Option<usize> {
+        memchr::memchr(byte, self)
+    }
+
+    pub fn rfind_byte(&self, byte: u8) -> Option<usize> {
+        memchr::memrchr(byte, self)
+    }
+
+    pub fn count_byte(&self, byte: u8) -> usize {
+        memchr::memchr_iter(byte, self).count()
+    }
+
+    // Like `split(|&b| b == byte)`: the separators are dropped and a trailing one yields a final
+    // empty piece.
+    pub fn split_on_byte(&self, byte: u8) -> impl Iterator<Item = &[u8]> + '_ {
+        let mut start = 0;
+        memchr::memchr_iter(byte, self)
+            .map(Some)
+            .chain(core::iter::once(None))
+            .map(move |end| {
+                let end = end.unwrap_or(self.len());
+                let piece = &self[start..end];
+                start = end + 1;
+                piece
+            })
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_helpers() {
+        let mut v = Vec::new();
+        v.extend(b"a,bc,,d,".iter().copied());
+        assert_eq!(v.find_byte(b','), Some(1));
+        assert_eq!(v.rfind_byte(b','), Some(7));
+        assert_eq!(v.find_byte(b'x'), None);
+        assert_eq!(v.count_byte(b','), 4);
+        let pieces: std::vec::Vec<&[u8]> = v.split_on_byte(b',').collect();
+        let expected: std::vec::Vec<&[u8]> = v.split(|&b| b == b',').collect();
+        assert_eq!(pieces, expected);
+        assert_eq!(Vec::new().split_on_byte(b',').count(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{IntoIter, Vec};
+use alloc::alloc::Global;
+use core::alloc::Allocator;
+
+// Owned runs of consecutive elements for which `pred(previous, next)` holds. Each run's length
+// is found on the remaining slice first, so every group is allocated exactly once and its
+// elements are moved, never cloned.
+pub struct IntoChunkBy<T, F, A: Allocator = Global> {
+    iter: IntoIter<T, A>,
+    pred: F,
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn into_chunk_by<F: FnMut(&T, &T) -> bool>(self, pred: F) -> IntoChunkBy<T, F, A> {
+        IntoChunkBy {
+            iter: self.into_iter(),
+            pred,
+        }
+    }
+}
+
+impl<T, F: FnMut(&T, &T) -> bool, A: Allocator + Clone> Iterator for IntoChunkBy<T, F, A> {
+    type Item = Vec<T, A>;
+
+    fn next(&mut self) -> Option<Vec<T, A>> {
+        let rest = self.iter.as_slice();
+        if rest.is_empty() {
+            return None;
+        }
+        let mut n = 1;
+        while n < rest.len() && (self.pred)(&rest[n - 1], &rest[n]) {
+            n += 1;
+        }
+        let mut group = Vec::with_capacity_in(n, self.iter._buf.alloc.clone());
+        for elem in self.iter.by_ref().take(n) {
+            unsafe { group.push_unchecked(elem) };
+        }
+        Some(group)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.as_slice().len();
+        (len.min(1), Some(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+    use alloc::string::String;
+
+    #[test]
+    fn into_chunk_by() {
+        let mut v = Vec::new();
+        for (k, name) in [(1, "a"), (1, "b"), (2, "c"), (3, "d"), (3, "e")].iter() {
+            v.push((*k, String::from(*name)));
+        }
+        let groups: Vec<Vec<(i32, String)>> = v.into_chunk_by(|a, b| a.0 == b.0).collect();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].len(), 2);
+        assert_eq!(groups[2][1].1, "e");
+        assert!(groups.iter().all(|g| g.capacity() == g.len()));
+
+        let ascending = Vec::from_fn(7, |i| [1, 2, 3, 2, 5, 1, 1][i]);
+        let runs: Vec<usize> = ascending
+            .into_chunk_by(|a, b| a < b)
+            .map(|g| g.len())
+            .collect();
+        assert_eq!(runs, [3, 2, 1, 1]);
+        assert_eq!(Vec::<u8>::new().into_chunk_by(|_, _| true).count(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{MemoryReport, Vec};
+use core::marker::PhantomData;
+
+const BLOCK_LEN: usize = 128;
+
+// Integers that can be stored in a `CompressedVec`. Values round-trip through a u64; signed
+// types are sign-extended so that small negative deltas stay small.
+pub trait CompressInt: Copy {
+    fn to_u64(self) -> u64;
+    fn from_u64(v: u64) -> Self;
+}
+
+macro_rules! compress_int {
+    ($($unsigned:ty),* ; $($signed:ty),*) => {
+        $(impl CompressInt for $unsigned {
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+            fn from_u64(v: u64) -> Self {
+                v as Self
+            }
+        })*
+        $(impl CompressInt for $signed {
+            fn to_u64(self) -> u64 {
+                self as i64 as u64
+            }
+            fn from_u64(v: u64) -> Self {
+                v as Self
+            }
+        })*
+    };
+}
+
+compress_int!(u8, u16, u32, u64, usize ; i8, i16, i32, i64, isize);
+
+// Append-only integer vector. Each value is stored as the zigzag-encoded difference from its
+// predecessor in LEB128 varint form; every `BLOCK_LEN`th value starts a block whose byte offset
+// and first value are kept in `blocks`, so random access decodes at most one block.
+pub struct CompressedVec<T> {
+    bytes: Vec<u8>,
+    blocks: Vec<(usize, u64)>,
+    last: u64,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: CompressInt> CompressedVec<T> {
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            blocks: Vec::new(),
+            last: 0,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, value: T) {
+        let value = value.to_u64();
+        if self.len.is_multiple_of(BLOCK_LEN) {
+            self.blocks.push((self.bytes.len(), value));
+        } else {
+            let delta = value.wrapping_sub(self.last) as i64;
+            write_varint(&mut self.bytes, ((delta << 1) ^ (delta >> 63)) as u64);
+        }
+        self.last = value;
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let (offset, first) = self.blocks[index / BLOCK_LEN];
+        let mut iter = Iter {
+            bytes: &self.bytes[offset..],
+            value: first,
+            remaining: index % BLOCK_LEN,
+            first: false,
+            _marker: PhantomData::<T>,
+        };
+        iter.by_ref().for_each(drop);
+        Some(T::from_u64(iter.value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.blocks
+            .iter()
+            .enumerate()
+            .flat_map(move |(b, &(offset, first))| Iter {
+                bytes: &self.bytes[offset..],
+                value: first,
+                remaining: BLOCK_LEN.min(self.len - b * BLOCK_LEN),
+                first: true,
+                _marker: PhantomData::<T>,
+            })
+    }
+}
+
+impl<T> MemoryReport for CompressedVec<T> {
+    // The compressed payload plus the block index.
+    fn allocated_bytes(&self) -> usize {
+        self.bytes.allocated_bytes() + self.blocks.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.bytes.used_bytes() + self.blocks.used_bytes()
+    }
+}
+
+impl<T: CompressInt> Default for CompressedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: CompressInt> Extend<T> for CompressedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: CompressInt> core::iter::FromIterator<T> for CompressedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+// Decodes `remaining` values of one block. The block's first value lives in the index, so only
+// the deltas after it are read from `bytes`.
+struct Iter<'a, T> {
+    bytes: &'a [u8],
+    value: u64,
+    remaining: usize,
+    first: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T: CompressInt> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if self.first {
+            self.first = false;
+        } else {
+            let zigzag = read_varint(&mut self.bytes);
+            let delta = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+            self.value = self.value.wrapping_add(delta as u64);
+        }
+        Some(T::from_u64(self.value))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> u64 {
+    let (mut v, mut shift, mut i) = (0u64, 0, 0);
+    loop {
+        let b = bytes[i];
+        i += 1;
+        v |= ((b & 0x7f) as u64) << shift;
+        if b < 0x80 {
+            break;
+        }
+        shift += 7;
+    }
+    *bytes = &bytes[i..];
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_and_random_access() {
+        let values = [0u64, 1, 5, 3, 1000, u64::MAX, 0, 42];
+        let mut v = CompressedVec::new();
+        for i in 0..1000 {
+            v.push(values[i % values.len()].wrapping_add(i as u64));
+        }
+        assert_eq!(v.len(), 1000);
+        for i in [0, 1, 127, 128, 129, 999] {
+            assert_eq!(
+                v.get(i),
+                Some(values[i % values.len()].wrapping_add(i as u64))
+            );
+        }
+        assert_eq!(v.get(1000), None);
+        assert!(v
+            .iter()
+            .eq((0..1000).map(|i| values[i % values.len()].wrapping_add(i as u64))));
+    }
+
+    #[test]
+    fn signed_and_small() {
+        let v: CompressedVec<i32> = (-500..500).rev().collect();
+        assert!(v.iter().eq((-500..500).rev()));
+        assert_eq!(v.get(999), Some(-500));
+        assert_eq!(v.get(0), Some(499));
+        // Deltas of -1 take one byte each.
+        assert!(v.used_bytes() < 1000 * 4 / 2);
+        assert!(v.allocated_bytes() >= v.used_bytes());
+    }
+}
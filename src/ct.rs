@@ -0,0 +1,36 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::hint::black_box;
+
+impl<A: Allocator> Vec<u8, A> {
+    // Compares without an early exit, so the time taken doesn't reveal where the first
+    // difference is. Only the length comparison is short-circuited; MACs and tokens have a
+    // public length.
+    pub fn ct_eq(&self, other: &[u8]) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let mut diff = 0u8;
+        for (a, b) in self.iter().zip(other) {
+            // Keeps the optimizer from turning the loop back into a short-circuiting compare
+            diff = black_box(diff | (a ^ b));
+        }
+        diff == 0
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+
+    #[test]
+    fn ct_eq() {
+        let mut a = Vec::new();
+        a.extend_from_slice(b"secret-tag");
+        assert!(a.ct_eq(b"secret-tag"));
+        assert!(!a.ct_eq(b"secret-taG"));
+        assert!(!a.ct_eq(b"Secret-tag"));
+        assert!(!a.ct_eq(b"secret"));
+        assert!(Vec::new().ct_eq(b""));
+    }
+}
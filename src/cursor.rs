@@ -0,0 +1,165 @@
+use crate::Vec;
+use alloc::alloc::Global;
+use core::alloc::Allocator;
+use core::ptr;
+
+// A gap buffer over the vector's own allocation: elements before the cursor stay at the front,
+// elements from the cursor on are parked at the end of the capacity, and edits at the cursor
+// only touch the gap between them. The tail is moved back into place once, on drop.
+//
+// While the cursor is alive `vec.len` only covers the front part, so leaking the cursor leaks
+// the tail instead of exposing uninitialized memory.
+pub struct CursorMut<'a, T, A: Allocator = Global> {
+    vec: &'a mut Vec<T, A>,
+    index: usize,
+    tail: usize,
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn cursor_mut(&mut self, index: usize) -> CursorMut<'_, T, A> {
+        self.check_poison();
+        assert!(index <= self.len, "index out of bounds");
+        let tail = self.len - index;
+        // The gap and the parked tail don't fit the ASAN marking of a prefix, so the whole
+        // spare capacity stays unpoisoned until the cursor is dropped
+        self.asan_unpoison(self.len..self.buf.cap);
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            ptr::copy(p.add(index), p.add(self.buf.cap - tail), tail);
+        }
+        self.len = index;
+        shadow!(self, stale);
+        CursorMut {
+            vec: self,
+            index,
+            tail,
+        }
+    }
+}
+
+impl<T, A: Allocator> CursorMut<'_, T, A> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index + self.tail
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn tail_ptr(&self) -> *mut T {
+        unsafe { self.vec.buf.ptr.as_ptr().add(self.vec.buf.cap - self.tail) }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        if self.tail == 0 {
+            None
+        } else {
+            Some(unsafe { &mut *self.tail_ptr() })
+        }
+    }
+
+    pub fn move_next(&mut self) -> bool {
+        if self.tail == 0 {
+            return false;
+        }
+        unsafe {
+            let elem = ptr::read(self.tail_ptr());
+            ptr::write(self.vec.buf.ptr.as_ptr().add(self.index), elem);
+        }
+        self.tail -= 1;
+        self.index += 1;
+        self.vec.len = self.index;
+        true
+    }
+
+    pub fn move_prev(&mut self) -> bool {
+        if self.index == 0 {
+            return false;
+        }
+        self.index -= 1;
+        self.vec.len = self.index;
+        self.tail += 1;
+        unsafe {
+            let elem = ptr::read(self.vec.buf.ptr.as_ptr().add(self.index));
+            ptr::write(self.tail_ptr(), elem);
+        }
+        true
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn insert_before(&mut self, elem: T) {
+        if self.index + self.tail == self.vec.buf.cap {
+            let old_cap = self.vec.buf.cap;
+            self.vec.buf.grow();
+            // The tail has to follow the end of the grown buffer
+            unsafe {
+                let p = self.vec.buf.ptr.as_ptr();
+                ptr::copy(p.add(old_cap - self.tail), self.tail_ptr(), self.tail);
+            }
+        }
+        unsafe { ptr::write(self.vec.buf.ptr.as_ptr().add(self.index), elem) };
+        self.index += 1;
+        self.vec.len = self.index;
+    }
+
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.tail == 0 {
+            return None;
+        }
+        let elem = unsafe { ptr::read(self.tail_ptr()) };
+        self.tail -= 1;
+        Some(elem)
+    }
+}
+
+impl<T, A: Allocator> Drop for CursorMut<'_, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            let dst = self.vec.buf.ptr.as_ptr().add(self.index);
+            ptr::copy(self.tail_ptr(), dst, self.tail);
+        }
+        self.vec.len = self.index + self.tail;
+        self.vec.poison_spare();
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+    use alloc::boxed::Box;
+
+    #[test]
+    fn edit() {
+        let mut a: Vec<Box<usize>> = (0..6).map(Box::new).collect();
+        {
+            let mut c = a.cursor_mut(2);
+            assert_eq!(c.current().map(|x| **x), Some(2));
+            assert_eq!(c.remove_current().map(|x| *x), Some(2));
+            for i in 10..20 {
+                c.insert_before(Box::new(i));
+            }
+            assert!(c.move_next());
+            assert!(c.move_prev());
+            assert!(c.move_prev());
+            assert_eq!(c.current().map(|x| **x), Some(19));
+            assert_eq!(c.len(), 15);
+        }
+        let got: std::vec::Vec<usize> = a.iter().map(|x| **x).collect();
+        let mut want = std::vec![0, 1];
+        want.extend(10..20);
+        want.extend(3..6);
+        assert_eq!(got, want);
+    }
+
+    #[test]
+    fn leak_cursor() {
+        let mut a: Vec<Box<usize>> = (0..4).map(Box::new).collect();
+        core::mem::forget(a.cursor_mut(1));
+        assert_eq!(a.len(), 1);
+        assert_eq!(*a[0], 0);
+    }
+}
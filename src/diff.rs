@@ -0,0 +1,266 @@
+use crate::Vec;
+use core::alloc::Allocator;
+
+// One step of an edit script. Indices refer to the vector as it is when the op is applied, so
+// ops must be applied in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffOp<T> {
+    Insert { index: usize, value: T },
+    Delete { index: usize },
+}
+
+enum Edit {
+    Keep,
+    Insert(usize),
+    Delete,
+}
+
+impl<T: PartialEq + Clone, A: Allocator> Vec<T, A> {
+    // Shortest edit script turning `self` into `other`, using the linear-space variant of
+    // Myers' O((N + M) D) algorithm.
+    pub fn diff(&self, other: &[T]) -> Vec<DiffOp<T>> {
+        let mut edits = Vec::new();
+        myers(self, other, 0, &mut edits);
+        let mut ops = Vec::new();
+        let mut pos = 0;
+        for edit in edits.iter() {
+            match *edit {
+                Edit::Keep => pos += 1,
+                Edit::Delete => ops.push(DiffOp::Delete { index: pos }),
+                Edit::Insert(j) => {
+                    ops.push(DiffOp::Insert {
+                        index: pos,
+                        value: other[j].clone(),
+                    });
+                    pos += 1;
+                }
+            }
+        }
+        ops
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn apply_diff<I: IntoIterator<Item = DiffOp<T>>>(&mut self, ops: I) {
+        for op in ops {
+            match op {
+                DiffOp::Insert { index, value } => self.insert(index, value),
+                DiffOp::Delete { index } => {
+                    self.remove(index);
+                }
+            }
+        }
+    }
+}
+
+// Appends the edits turning `a` into `b`, in order. `b_start` is the offset of `b` in the whole
+// second sequence, which `Edit::Insert` indices refer to. Splitting at the middle snake halves
+// the edit distance on each level, so only the two diagonal arrays of one search are live at a
+// time and memory stays O(N + M).
+fn myers<T: PartialEq>(a: &[T], b: &[T], b_start: usize, edits: &mut Vec<Edit>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    for _ in 0..prefix {
+        edits.push(Edit::Keep);
+    }
+    let (a, b, b_start) = (&a[prefix..], &b[prefix..], b_start + prefix);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    match middle_snake(a, b) {
+        Some((x, y)) => {
+            myers(&a[..x], &b[..y], b_start, edits);
+            myers(&a[x..], &b[y..], b_start + y, edits);
+        }
+        None => {
+            for _ in 0..a.len() {
+                edits.push(Edit::Delete);
+            }
+            for j in 0..b.len() {
+                edits.push(Edit::Insert(b_start + j));
+            }
+        }
+    }
+    for _ in 0..suffix {
+        edits.push(Edit::Keep);
+    }
+}
+
+// Runs the search from both ends at once and returns a point on a shortest edit path where the
+// two meet, or `None` if the sequences have nothing in common (or one is empty) and the script is
+// just deletes followed by inserts. Expects common prefixes and suffixes to be stripped.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    if n == 0 || m == 0 {
+        return None;
+    }
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d + 1;
+    let len = (2 * offset + 1) as usize;
+    // Furthest x per diagonal, forward from the start and backward from the end; -1 is unreached
+    let (mut fwd, mut bwd) = (Vec::with_capacity(len), Vec::with_capacity(len));
+    for _ in 0..len {
+        fwd.push(-1isize);
+        bwd.push(-1isize);
+    }
+    fwd[offset as usize + 1] = 0;
+    bwd[offset as usize + 1] = 0;
+    let delta = n - m;
+    // With an odd delta the paths can only meet after a forward step, otherwise after a
+    // backward one
+    let front = delta % 2 != 0;
+    // Diagonals that ran off the grid are skipped from then on
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let i = (offset + k1) as usize;
+            let mut x = if k1 == -d || (k1 != d && fwd[i - 1] < fwd[i + 1]) {
+                fwd[i + 1]
+            } else {
+                fwd[i - 1] + 1
+            };
+            let mut y = x - k1;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            fwd[i] = x;
+            if x > n {
+                k1_end += 2;
+            } else if y > m {
+                k1_start += 2;
+            } else if front {
+                let j = offset + delta - k1;
+                if j >= 0 && j < len as isize && bwd[j as usize] != -1 && x >= n - bwd[j as usize] {
+                    return Some((x as usize, y as usize));
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let i = (offset + k2) as usize;
+            let mut x = if k2 == -d || (k2 != d && bwd[i - 1] < bwd[i + 1]) {
+                bwd[i + 1]
+            } else {
+                bwd[i - 1] + 1
+            };
+            let mut y = x - k2;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            bwd[i] = x;
+            if x > n {
+                k2_end += 2;
+            } else if y > m {
+                k2_start += 2;
+            } else if !front {
+                let j = offset + delta - k2;
+                if j >= 0 && j < len as isize && fwd[j as usize] != -1 {
+                    let x1 = fwd[j as usize];
+                    let y1 = x1 - (delta - k2);
+                    if x1 >= n - x {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vec_of(s: &str) -> Vec<u8> {
+        let mut v = Vec::new();
+        v.extend(s.bytes());
+        v
+    }
+
+    #[test]
+    fn roundtrip() {
+        let cases = [
+            ("ABCABBA", "CBABAC"),
+            ("", "abc"),
+            ("abc", ""),
+            ("", ""),
+            ("same", "same"),
+            ("kitten", "sitting"),
+        ];
+        for (a, b) in cases {
+            let (mut a, b) = (vec_of(a), vec_of(b));
+            let ops = a.diff(&b);
+            a.apply_diff(ops);
+            assert_eq!(&a[..], &b[..]);
+        }
+    }
+
+    #[test]
+    fn script_is_minimal() {
+        // The classic example from Myers' paper has edit distance 5.
+        assert_eq!(vec_of("ABCABBA").diff(&vec_of("CBABAC")).len(), 5);
+        assert!(vec_of("same").diff(&vec_of("same")).is_empty());
+        assert_eq!(
+            &vec_of("ac").diff(&vec_of("abc"))[..],
+            &[DiffOp::Insert {
+                index: 1,
+                value: b'b'
+            }]
+        );
+    }
+
+    // Insert/delete distance from the LCS table, to check minimality on many small inputs.
+    fn distance(a: &[u8], b: &[u8]) -> usize {
+        let mut lcs = std::vec![std::vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                lcs[i + 1][j + 1] = if a[i] == b[j] {
+                    lcs[i][j] + 1
+                } else {
+                    lcs[i][j + 1].max(lcs[i + 1][j])
+                };
+            }
+        }
+        a.len() + b.len() - 2 * lcs[a.len()][b.len()]
+    }
+
+    #[test]
+    fn minimal_on_random_inputs() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |bound: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % bound) as u8
+        };
+        for _ in 0..500 {
+            let (la, lb) = (next(20), next(20));
+            let mut a: Vec<u8> = (0..la).map(|_| next(3)).collect();
+            let b: Vec<u8> = (0..lb).map(|_| next(3)).collect();
+            let ops = a.diff(&b);
+            assert_eq!(ops.len(), distance(&a, &b));
+            a.apply_diff(ops);
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn nothing_in_common() {
+        let mut a: Vec<u32> = (0..3000).collect();
+        let b: Vec<u32> = (3000..5000).collect();
+        let ops = a.diff(&b);
+        assert_eq!(ops.len(), 5000);
+        a.apply_diff(ops);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::fmt;
+
+// Formats the elements separated by `sep`, with no brackets. Width, precision and the other
+// flags apply to each element rather than to the whole list.
+pub struct VecDisplay<'a, T> {
+    elems: &'a [T],
+    sep: &'a str,
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn display<'a>(&'a self, sep: &'a str) -> VecDisplay<'a, T> {
+        VecDisplay { elems: self, sep }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for VecDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, elem) in self.elems.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.sep)?;
+            }
+            elem.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+    use alloc::format;
+
+    #[test]
+    fn display() {
+        let mut v = Vec::new();
+        assert_eq!(format!("[{}]", v.display(", ")), "[]");
+        v.extend_from_slice(&[1.5f64, 2.0, 3.25]);
+        assert_eq!(format!("{}", v.display(", ")), "1.5, 2, 3.25");
+        assert_eq!(format!("{:.1}", v.display("|")), "1.5|2.0|3.2");
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{mmap, Vec};
+use core::alloc::{AllocError, Allocator, Layout};
+use core::mem;
+use core::ptr::NonNull;
+
+// An allocator that reserves `reserve` bytes of address space per buffer up front and commits
+// pages as the buffer grows. Growing only changes page protections, so the buffer never moves
+// and no element is ever copied; shrinking hands the freed pages back to the kernel. Asking for
+// more than the reservation fails like any other allocation failure.
+//
+// The reservation costs address space only, so it can be far larger than physical memory.
+#[derive(Clone, Copy, Debug)]
+pub struct ElasticAlloc {
+    reserve: usize,
+}
+
+// A vector whose buffer stays in place for its whole life, for append-heavy workloads where
+// reallocation copies dominate.
+pub type ElasticVec<T> = Vec<T, ElasticAlloc>;
+
+impl ElasticAlloc {
+    pub const fn new(reserve: usize) -> Self {
+        Self { reserve }
+    }
+
+    pub fn reserve(&self) -> usize {
+        self.reserve
+    }
+
+    fn reserved_len(&self) -> Result<usize, AllocError> {
+        mmap::round_up(self.reserve, mmap::page_size()).ok_or(AllocError)
+    }
+}
+
+fn committed_len(size: usize) -> usize {
+    mmap::round_up(size, mmap::page_size()).unwrap()
+}
+
+unsafe impl Allocator for ElasticAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let reserved = self.reserved_len()?;
+        if layout.size() > reserved || layout.align() > mmap::page_size() {
+            return Err(AllocError);
+        }
+        let ptr = mmap::reserve(reserved)?;
+        let len = committed_len(layout.size());
+        unsafe {
+            if let Err(e) = mmap::commit(ptr.as_ptr() as usize, len) {
+                mmap::unmap(ptr, reserved);
+                return Err(e);
+            }
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        mmap::unmap(ptr, self.reserved_len().unwrap());
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if new_layout.size() > self.reserved_len()? || new_layout.align() > mmap::page_size() {
+            return Err(AllocError);
+        }
+        let (old_len, new_len) = (
+            committed_len(old_layout.size()),
+            committed_len(new_layout.size()),
+        );
+        if new_len > old_len {
+            mmap::commit(ptr.as_ptr() as usize + old_len, new_len - old_len)?;
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_len))
+    }
+
+    // The default would allocate elsewhere and copy; zero the new part in place instead.
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new = self.grow(ptr, old_layout, new_layout)?;
+        let fresh = new_layout.size() - old_layout.size();
+        ptr.as_ptr().add(old_layout.size()).write_bytes(0, fresh);
+        Ok(new)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_len, new_len) = (
+            committed_len(old_layout.size()),
+            committed_len(new_layout.size()),
+        );
+        if new_len < old_len {
+            let tail = ptr.as_ptr().add(new_len);
+            mmap::advise(tail, old_len - new_len, libc::MADV_DONTNEED);
+            mmap::protect_none(tail as usize, old_len - new_len);
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_len))
+    }
+}
+
+impl<T> Vec<T, ElasticAlloc> {
+    // Room for up to `max_len` elements without the buffer ever moving.
+    pub fn elastic(max_len: usize) -> Self {
+        let reserve = max_len.saturating_mul(mem::size_of::<T>());
+        Vec::new_in(ElasticAlloc::new(reserve))
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use crate::TryReserveError;
+
+    #[test]
+    fn grows_in_place() {
+        let mut v: ElasticVec<u64> = Vec::elastic(1 << 30);
+        v.push(0);
+        let ptr = v.as_ptr();
+        for i in 1..1_000_000 {
+            v.push(i);
+        }
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v[999_999], 999_999);
+        v.truncate(10);
+        v.shrink_to_fit();
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(&v[..3], &[0, 1, 2]);
+        for i in 10..100_000 {
+            v.push(i);
+        }
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v.len(), 100_000);
+    }
+
+    // Doubling overshoots a reservation that isn't a power of two; the last step is exact.
+    #[test]
+    fn fills_the_whole_reservation() {
+        let max = 3 * mmap::page_size();
+        let mut v: ElasticVec<u8> = Vec::elastic(max);
+        for i in 0..max {
+            v.push(i as u8);
+        }
+        assert_eq!(v.capacity(), max);
+        assert!(v.try_push(0).is_err());
+        assert_eq!(v.len(), max);
+    }
+
+    #[test]
+    fn reservation_is_a_hard_limit() {
+        let mut v: ElasticVec<u8> = Vec::elastic(mmap::page_size());
+        assert!(v.try_reserve(mmap::page_size()).is_ok());
+        assert!(matches!(
+            v.try_reserve(mmap::page_size() + 1),
+            Err(TryReserveError::AllocError { .. })
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::Vec;
+use alloc::string::String;
+use core::alloc::Allocator;
+use core::fmt;
+
+// Hex (lowercase out, either case in) and standard padded base64. Each direction computes the
+// exact output length first and allocates once.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidLength,
+    InvalidByte { index: usize, byte: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength => f.write_str("invalid input length"),
+            DecodeError::InvalidByte { index, byte } => {
+                write!(f, "invalid byte {:#04x} at offset {}", byte, index)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn hex_digit(s: &[u8], index: usize) -> Result<u8, DecodeError> {
+    let byte = s[index];
+    match byte {
+        b'0'..=b'9' => Ok(byte - b'0'),
+        b'a'..=b'f' => Ok(byte - b'a' + 10),
+        b'A'..=b'F' => Ok(byte - b'A' + 10),
+        _ => Err(DecodeError::InvalidByte { index, byte }),
+    }
+}
+
+fn base64_digit(s: &[u8], index: usize) -> Result<u32, DecodeError> {
+    let byte = s[index];
+    match byte {
+        b'A'..=b'Z' => Ok((byte - b'A') as u32),
+        b'a'..=b'z' => Ok((byte - b'a' + 26) as u32),
+        b'0'..=b'9' => Ok((byte - b'0' + 52) as u32),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(DecodeError::InvalidByte { index, byte }),
+    }
+}
+
+// Every byte pushed is ASCII.
+fn ascii_string(v: Vec<u8>) -> String {
+    unsafe { String::from_utf8_unchecked(v.into()) }
+}
+
+impl<A: Allocator> Vec<u8, A> {
+    pub fn to_hex(&self) -> String {
+        let mut out = Vec::with_capacity(self.len() * 2);
+        for &b in self.iter() {
+            out.push(HEX[(b >> 4) as usize]);
+            out.push(HEX[(b & 0xf) as usize]);
+        }
+        ascii_string(out)
+    }
+
+    pub fn to_base64(&self) -> String {
+        let mut out = Vec::with_capacity(self.len().div_ceil(3) * 4);
+        for chunk in self.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize]);
+                } else {
+                    out.push(b'=');
+                }
+            }
+        }
+        ascii_string(out)
+    }
+}
+
+impl Vec<u8> {
+    pub fn from_hex(s: &str) -> Result<Self, DecodeError> {
+        let s = s.as_bytes();
+        if !s.len().is_multiple_of(2) {
+            return Err(DecodeError::InvalidLength);
+        }
+        let mut out = Vec::with_capacity(s.len() / 2);
+        for i in (0..s.len()).step_by(2) {
+            out.push(hex_digit(s, i)? << 4 | hex_digit(s, i + 1)?);
+        }
+        Ok(out)
+    }
+
+    // Padding is required, and `=` is only accepted at the end.
+    pub fn from_base64(s: &str) -> Result<Self, DecodeError> {
+        let s = s.as_bytes();
+        if !s.len().is_multiple_of(4) {
+            return Err(DecodeError::InvalidLength);
+        }
+        let padding = s.iter().rev().take(2).take_while(|&&b| b == b'=').count();
+        let mut out = Vec::with_capacity(s.len() / 4 * 3 - padding);
+        for c in 0..s.len() / 4 {
+            let last = (c + 1) * 4 == s.len();
+            let digits = if last { 4 - padding } else { 4 };
+            let mut n = 0u32;
+            for i in 0..digits {
+                n |= base64_digit(s, c * 4 + i)? << (18 - 6 * i);
+            }
+            for i in 0..digits - 1 {
+                out.push((n >> (16 - 8 * i)) as u8);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(b: &[u8]) -> Vec<u8> {
+        let mut v = Vec::new();
+        v.extend_from_slice(b);
+        v
+    }
+
+    #[test]
+    fn hex() {
+        let v = bytes(&[0x00, 0x7f, 0xab, 0xff]);
+        assert_eq!(v.to_hex(), "007fabff");
+        assert_eq!(Vec::from_hex("007FabfF").unwrap(), v);
+        assert_eq!(Vec::from_hex("abc"), Err(DecodeError::InvalidLength));
+        assert_eq!(
+            Vec::from_hex("0g"),
+            Err(DecodeError::InvalidByte {
+                index: 1,
+                byte: b'g'
+            })
+        );
+    }
+
+    #[test]
+    fn base64() {
+        for (plain, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xfb\xff", "+/8="),
+        ] {
+            assert_eq!(bytes(plain).to_base64(), encoded);
+            assert_eq!(Vec::from_base64(encoded).unwrap(), bytes(plain));
+        }
+        assert_eq!(Vec::from_base64("Zg="), Err(DecodeError::InvalidLength));
+        assert!(matches!(
+            Vec::from_base64("Zg==Zg=="),
+            Err(DecodeError::InvalidByte { index: 2, .. })
+        ));
+        assert!(Vec::from_base64("Z===").is_err());
+    }
+}
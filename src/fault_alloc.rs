@@ -0,0 +1,222 @@
+use alloc::alloc::Global;
+use core::alloc::{AllocError, Allocator, Layout};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+// Test support: a `Global` wrapper that fails requests on demand, so the `try_*` paths and the
+// cleanup after a failed allocation can be exercised deterministically. Share it by reference
+// (`Vec::new_in(&alloc)`) to keep programming it while vectors use it. Allocating, growing and
+// shrinking count as requests; frees always go through.
+pub struct FaultyAlloc {
+    requests: AtomicUsize,
+    fail_at: AtomicUsize,
+    max_size: AtomicUsize,
+    live_bytes: AtomicUsize,
+}
+
+impl FaultyAlloc {
+    pub const fn new() -> Self {
+        Self {
+            requests: AtomicUsize::new(0),
+            fail_at: AtomicUsize::new(usize::MAX),
+            max_size: AtomicUsize::new(usize::MAX),
+            live_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    // Lets `n` more requests through and fails the one after that, once.
+    pub fn fail_after(&self, n: usize) {
+        let next = self.requests.load(Ordering::Relaxed);
+        self.fail_at.store(next + n, Ordering::Relaxed);
+    }
+
+    // Fails every request for more than `size` bytes until `reset`.
+    pub fn fail_above(&self, size: usize) {
+        self.max_size.store(size, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.fail_at.store(usize::MAX, Ordering::Relaxed);
+        self.max_size.store(usize::MAX, Ordering::Relaxed);
+    }
+
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    // Bytes currently handed out; back to zero once every user has freed its memory.
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes.load(Ordering::Relaxed)
+    }
+
+    fn admit(&self, layout: Layout) -> Result<(), AllocError> {
+        let n = self.requests.fetch_add(1, Ordering::Relaxed);
+        if n == self.fail_at.load(Ordering::Relaxed)
+            || layout.size() > self.max_size.load(Ordering::Relaxed)
+        {
+            return Err(AllocError);
+        }
+        Ok(())
+    }
+}
+
+impl Default for FaultyAlloc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Allocator for FaultyAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit(layout)?;
+        let p = Global.allocate(layout)?;
+        self.live_bytes.fetch_add(layout.size(), Ordering::Relaxed);
+        Ok(p)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live_bytes.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit(new_layout)?;
+        let p = unsafe { Global.grow(ptr, old_layout, new_layout)? };
+        let delta = new_layout.size() - old_layout.size();
+        self.live_bytes.fetch_add(delta, Ordering::Relaxed);
+        Ok(p)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit(new_layout)?;
+        let p = unsafe { Global.shrink(ptr, old_layout, new_layout)? };
+        let delta = old_layout.size() - new_layout.size();
+        self.live_bytes.fetch_sub(delta, Ordering::Relaxed);
+        Ok(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ShrinkPolicy, TryReserveError, Vec};
+    use alloc::rc::Rc;
+
+    fn is_alloc_error<T>(r: Result<T, TryReserveError>) -> bool {
+        matches!(r, Err(TryReserveError::AllocError { .. }))
+    }
+
+    #[test]
+    fn fail_nth_request() {
+        let alloc = FaultyAlloc::new();
+        let mut v = Vec::new_in(&alloc);
+        alloc.fail_after(0);
+        assert!(is_alloc_error(v.try_reserve(4)));
+        assert_eq!(v.capacity(), 0);
+        // Only the one request fails
+        for i in 0..8 {
+            v.try_push(i).unwrap();
+        }
+        let (cap, ptr) = (v.capacity(), v.as_ptr());
+        alloc.fail_after(0);
+        assert!(is_alloc_error(v.try_reserve(cap)));
+        assert_eq!((v.capacity(), v.as_ptr()), (cap, ptr));
+        alloc.fail_after(1);
+        v.try_reserve(cap).unwrap();
+        assert!(is_alloc_error(v.try_reserve(2 * v.capacity() - v.len())));
+        assert_eq!(v[7], 7);
+        // A failed doubling is retried once at the exact size
+        let mut w = Vec::new_in(&alloc);
+        for i in 0..4 {
+            w.try_push(i).unwrap();
+        }
+        alloc.fail_after(0);
+        w.try_push(4u32).unwrap();
+        assert_eq!(w.capacity(), 5);
+    }
+
+    #[test]
+    fn fallible_extends_keep_what_fits() {
+        let alloc = FaultyAlloc::new();
+        let mut v = Vec::new_in(&alloc);
+        v.try_extend_from_slice(&[1u32, 2, 3]).unwrap();
+        v.try_extend(4..6).unwrap();
+        assert_eq!(v[..], [1, 2, 3, 4, 5]);
+
+        alloc.fail_above(0);
+        let cap = v.capacity();
+        let mut rest = 6..100;
+        let (e, elem) = v.try_extend(rest.by_ref()).unwrap_err();
+        assert!(is_alloc_error::<()>(Err(e)));
+        assert_eq!(v.len(), cap);
+        assert_eq!(elem, cap as u32 + 1);
+        assert_eq!(rest.next(), Some(elem + 1));
+        assert!(is_alloc_error(v.try_extend_from_slice(&[0])));
+        assert_eq!(v.try_push(7).unwrap_err().1, 7);
+        assert_eq!(v.len(), cap);
+    }
+
+    #[test]
+    fn fail_above_size() {
+        let alloc = FaultyAlloc::new();
+        alloc.fail_above(64);
+        assert!(is_alloc_error(Vec::<u8, _>::try_with_capacity_in(
+            65, &alloc
+        )));
+        let mut v = Vec::<u8, _>::try_with_capacity_in(64, &alloc).unwrap();
+        assert!(is_alloc_error(v.try_reserve(65)));
+        assert_eq!(v.capacity(), 64);
+        alloc.reset();
+        v.try_reserve(65).unwrap();
+        drop(v);
+        assert_eq!(alloc.live_bytes(), 0);
+    }
+
+    #[test]
+    fn failures_leave_contents_intact() {
+        let alloc = FaultyAlloc::new();
+        let item = Rc::new(());
+        {
+            let mut v = Vec::new_in(&alloc);
+            for _ in 0..4 {
+                v.try_push(Rc::clone(&item)).unwrap();
+            }
+            while v.len() < v.capacity() {
+                v.try_push(Rc::clone(&item)).unwrap();
+            }
+            let len = v.len();
+            alloc.fail_above(0);
+            let rejected = Rc::clone(&item);
+            let (e, rejected) = v.try_insert(0, rejected).unwrap_err();
+            assert!(is_alloc_error::<()>(Err(e)));
+            assert_eq!(v.len(), len);
+            assert_eq!(Rc::strong_count(&item), len + 2);
+            drop(rejected);
+
+            // Failed shrinks keep the old buffer
+            v.set_shrink_policy(Some(ShrinkPolicy::below_fraction(2)));
+            let cap = v.capacity();
+            alloc.fail_above(0);
+            while v.len() > 1 {
+                drop(v.pop());
+            }
+            assert_eq!(v.capacity(), cap);
+            assert_eq!(Rc::strong_count(&item), 2);
+            alloc.reset();
+            drop(v.pop());
+            assert!(v.capacity() < cap);
+        }
+        assert_eq!(Rc::strong_count(&item), 1);
+        assert_eq!(alloc.live_bytes(), 0);
+    }
+}
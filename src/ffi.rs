@@ -0,0 +1,104 @@
+//! C interface for byte vectors.
+//!
+//! Ownership rules:
+//!
+//! - A `ByteVec *` returned by `vec_new` or `vec_from_raw_parts` is owned by the caller and must
+//!   be released exactly once, with `vec_free` or `vec_into_raw_parts`.
+//! - Pointers obtained from `vec_data` borrow the buffer; they are invalidated by the next
+//!   `vec_push_u8` (which may reallocate) and by freeing the vector.
+//! - `VecRawParts` taken out with `vec_into_raw_parts` still owns the allocation. It must be
+//!   given back through `vec_from_raw_parts` so that Rust frees it; never `free()` it from C.
+use crate::Vec;
+use alloc::boxed::Box;
+
+pub type ByteVec = Vec<u8>;
+
+#[repr(C)]
+pub struct VecRawParts {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub cap: usize,
+}
+
+#[no_mangle]
+pub extern "C" fn vec_new() -> *mut ByteVec {
+    Box::into_raw(Box::new(Vec::new()))
+}
+
+/// # Safety
+///
+/// `v` must be a live pointer returned by this module.
+#[no_mangle]
+pub unsafe extern "C" fn vec_push_u8(v: *mut ByteVec, byte: u8) {
+    unsafe { (*v).push(byte) }
+}
+
+/// # Safety
+///
+/// `v` must be a live pointer returned by this module.
+#[no_mangle]
+pub unsafe extern "C" fn vec_data(v: *mut ByteVec) -> *mut u8 {
+    unsafe { (&mut *v).as_mut_ptr() }
+}
+
+/// # Safety
+///
+/// `v` must be a live pointer returned by this module.
+#[no_mangle]
+pub unsafe extern "C" fn vec_len(v: *const ByteVec) -> usize {
+    unsafe { (&*v).len() }
+}
+
+/// # Safety
+///
+/// `v` must be null or a live pointer returned by this module; it is dangling afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn vec_free(v: *mut ByteVec) {
+    if !v.is_null() {
+        drop(unsafe { Box::from_raw(v) });
+    }
+}
+
+/// # Safety
+///
+/// `v` must be a live pointer returned by this module; it is dangling afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn vec_into_raw_parts(v: *mut ByteVec) -> VecRawParts {
+    let v = unsafe { Box::from_raw(v) };
+    let (ptr, len, cap) = v.into_raw_parts();
+    VecRawParts { ptr, len, cap }
+}
+
+/// # Safety
+///
+/// `parts` must have come from `vec_into_raw_parts` and not been used to rebuild a vector
+/// since.
+#[no_mangle]
+pub unsafe extern "C" fn vec_from_raw_parts(parts: VecRawParts) -> *mut ByteVec {
+    let v = unsafe { Vec::from_raw_parts(parts.ptr, parts.len, parts.cap) };
+    Box::into_raw(Box::new(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr;
+
+    #[test]
+    fn roundtrip() {
+        unsafe {
+            let v = vec_new();
+            for b in b"abc" {
+                vec_push_u8(v, *b);
+            }
+            assert_eq!(vec_len(v), 3);
+            *vec_data(v) = b'x';
+            let parts = vec_into_raw_parts(v);
+            assert_eq!(core::slice::from_raw_parts(parts.ptr, parts.len), b"xbc");
+            let v = vec_from_raw_parts(parts);
+            assert_eq!(&(&*v)[..], b"xbc");
+            vec_free(v);
+            vec_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{MemoryReport, Vec};
+use core::ops::{Index, IndexMut};
+
+// A row-major 2D array over one flat vector: cell (r, c) lives at `r * cols + c`.
+pub struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(rows: usize, cols: usize, mut f: F) -> Self {
+        let len = rows.checked_mul(cols).expect("capacity overflow");
+        let mut data = Vec::with_capacity(len);
+        for r in 0..rows {
+            for c in 0..cols {
+                data.push(f(r, c));
+            }
+        }
+        Self { data, rows, cols }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> Option<&T> {
+        if r < self.rows && c < self.cols {
+            Some(&self.data[r * self.cols + c])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, r: usize, c: usize) -> Option<&mut T> {
+        if r < self.rows && c < self.cols {
+            Some(&mut self.data[r * self.cols + c])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        assert!(r < self.rows, "row out of bounds");
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [T] {
+        assert!(r < self.rows, "row out of bounds");
+        &mut self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // Not `chunks_exact`, which panics on zero columns; a grid without columns still has
+        // `rows` empty rows
+        (0..self.rows).map(move |r| self.row(r))
+    }
+
+    pub fn column(&self, c: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(c < self.cols, "column out of bounds");
+        self.data[c..].iter().step_by(self.cols)
+    }
+
+    pub fn as_flat_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_flat_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(rows: usize, cols: usize, value: T) -> Self {
+        Self::from_fn(rows, cols, |_, _| value.clone())
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.data.fill(value);
+    }
+
+    // Changes the shape, keeping every cell that is still inside it at the same (r, c) and
+    // filling new cells with `value`.
+    pub fn resize(&mut self, rows: usize, cols: usize, value: T) {
+        if cols == self.cols {
+            let len = rows.checked_mul(cols).expect("capacity overflow");
+            self.data.truncate(len);
+            while self.data.len() < len {
+                self.data.push(value.clone());
+            }
+            self.rows = rows;
+            return;
+        }
+        let old = core::mem::replace(self, Self::new(rows, cols, value));
+        let (keep_rows, keep_cols) = (rows.min(old.rows), cols.min(old.cols));
+        let old_cols = old.cols;
+        for (i, x) in old.data.into_iter().enumerate() {
+            let (r, c) = (i / old_cols, i % old_cols);
+            if r < keep_rows && c < keep_cols {
+                self.data[r * cols + c] = x;
+            }
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        self.get(r, c).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut T {
+        self.get_mut(r, c).expect("index out of bounds")
+    }
+}
+
+impl<T> MemoryReport for Grid<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.data.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.data.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(g: &Grid<usize>) -> std::vec::Vec<std::vec::Vec<usize>> {
+        g.iter_rows().map(|r| r.to_vec()).collect()
+    }
+
+    #[test]
+    fn access() {
+        let mut g = Grid::from_fn(2, 3, |r, c| r * 10 + c);
+        assert_eq!(g[(1, 2)], 12);
+        assert_eq!(g.get(2, 0), None);
+        assert_eq!(g.get(0, 3), None);
+        g[(0, 1)] = 99;
+        g.row_mut(1)[0] = 7;
+        assert_eq!(cells(&g), [[0, 99, 2], [7, 11, 12]]);
+        assert!(g.column(1).copied().eq([99, 11]));
+        g.fill(1);
+        assert!(g.as_flat_slice().iter().all(|&x| x == 1));
+    }
+
+    #[test]
+    fn resize_keeps_cells() {
+        let mut g = Grid::from_fn(2, 2, |r, c| r * 10 + c + 1);
+        g.resize(3, 2, 0);
+        assert_eq!(cells(&g), [[1, 2], [11, 12], [0, 0]]);
+        g.resize(2, 3, 5);
+        assert_eq!(cells(&g), [[1, 2, 5], [11, 12, 5]]);
+        g.resize(1, 1, 0);
+        assert_eq!(cells(&g), [[1]]);
+        g.resize(0, 4, 0);
+        assert_eq!((g.rows(), g.cols(), g.iter_rows().count()), (0, 4, 0));
+        g.resize(3, 0, 0);
+        assert_eq!(
+            (g.iter_rows().count(), g.iter_rows().all(<[_]>::is_empty)),
+            (3, true)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::mmap;
+use core::alloc::{AllocError, Allocator, Layout};
+use core::ptr::NonNull;
+
+// A debugging allocator that surrounds every buffer with inaccessible pages. The buffer is
+// placed at the end of its data pages, so the first byte past `cap` elements is the first byte
+// of the trailing guard page and a raw write (or read) one element out of bounds faults
+// immediately. A leading guard page in front of the data pages catches underflows that run past
+// them. Resizing always moves to a new mapping and unmaps the old one, so stale pointers into a
+// reallocated buffer fault too.
+//
+// Costs at least three pages and two syscalls per allocation: for tests and debugging only.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GuardPageAlloc;
+
+// Start of the mapping and its total length for a buffer of `size` bytes at `ptr`.
+fn mapping(ptr: usize, size: usize) -> (usize, usize) {
+    let page = mmap::page_size();
+    let data_len = mmap::round_up(size, page).unwrap();
+    let trailing_guard = mmap::round_up(ptr + size, page).unwrap();
+    (trailing_guard - data_len - page, data_len + 2 * page)
+}
+
+unsafe impl Allocator for GuardPageAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let page = mmap::page_size();
+        if layout.align() > page {
+            return Err(AllocError);
+        }
+        let data_len = mmap::round_up(layout.size(), page).ok_or(AllocError)?;
+        let total = data_len.checked_add(2 * page).ok_or(AllocError)?;
+        let base = mmap::map_aligned(total, page)?.as_ptr() as usize;
+        unsafe {
+            mmap::protect_none(base, page);
+            mmap::protect_none(base + page + data_len, page);
+        }
+        // Array layouts are a multiple of their alignment, so this ends right at the guard
+        let start = (base + page + data_len - layout.size()) & !(layout.align() - 1);
+        let ptr = unsafe { NonNull::new_unchecked(start as *mut u8) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let (base, total) = mapping(ptr.as_ptr() as usize, layout.size());
+        mmap::unmap(NonNull::new_unchecked(base as *mut u8), total);
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use crate::Vec;
+
+    #[test]
+    fn behaves_like_a_vector() {
+        let mut v = Vec::new_in(GuardPageAlloc);
+        for i in 0..5000u32 {
+            v.push(i);
+            let end = v.as_ptr() as usize + v.capacity() * 4;
+            assert_eq!(end % mmap::page_size(), 0);
+        }
+        v.truncate(3);
+        v.shrink_to_fit();
+        assert_eq!(&v[..], &[0, 1, 2]);
+    }
+
+    // Runs `f` in a forked child and reports whether it died from a memory fault.
+    fn faults(f: impl FnOnce()) -> bool {
+        unsafe {
+            let pid = libc::fork();
+            assert!(pid >= 0);
+            if pid == 0 {
+                f();
+                libc::_exit(0);
+            }
+            let mut status = 0;
+            libc::waitpid(pid, &mut status, 0);
+            libc::WIFSIGNALED(status)
+                && matches!(libc::WTERMSIG(status), libc::SIGSEGV | libc::SIGBUS)
+        }
+    }
+
+    #[test]
+    fn out_of_bounds_faults() {
+        let mut v: Vec<u64, _> = Vec::with_capacity_in(3, GuardPageAlloc);
+        v.push(1);
+        // Unpoisons the spare slots for ASAN builds; only the guard page should catch this
+        v.spare_capacity_mut();
+        let p = v.as_mut_ptr();
+        assert!(!faults(|| unsafe { p.add(2).write_volatile(7) }));
+        assert!(faults(|| unsafe { p.add(3).write_volatile(7) }));
+        let data_start = (p as usize & !(mmap::page_size() - 1)) as *mut u8;
+        assert!(!faults(|| unsafe { data_start.write_volatile(7) }));
+        assert!(faults(|| unsafe { data_start.sub(1).write_volatile(7) }));
+    }
+}
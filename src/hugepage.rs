@@ -0,0 +1,171 @@
+use crate::mmap;
+use alloc::alloc::Global;
+use core::alloc::{AllocError, Allocator, Layout};
+use core::ptr::{self, NonNull};
+
+pub const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+// An allocator for very large vectors. Buffers of at least `HUGE_PAGE_SIZE` bytes get their own
+// huge-page aligned mapping marked `MADV_HUGEPAGE`, so transparent huge pages can back them;
+// smaller buffers come from the global allocator. Which path a buffer took follows from its
+// layout size alone, so both can be told apart on every later call.
+//
+// Growing a mapping uses `mremap`, which moves pages instead of copying bytes. Shrinking unmaps
+// whole huge pages past the new end and hands the remaining freed tail back to the kernel with
+// `MADV_DONTNEED`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HugePageAlloc;
+
+fn mapped_len(size: usize) -> Result<usize, AllocError> {
+    mmap::round_up(size, HUGE_PAGE_SIZE).ok_or(AllocError)
+}
+
+fn is_mapped(layout: Layout) -> bool {
+    layout.size() >= HUGE_PAGE_SIZE
+}
+
+unsafe impl Allocator for HugePageAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !is_mapped(layout) {
+            return Global.allocate(layout);
+        }
+        if layout.align() > HUGE_PAGE_SIZE {
+            return Err(AllocError);
+        }
+        let len = mapped_len(layout.size())?;
+        let ptr = mmap::map_aligned(len, HUGE_PAGE_SIZE)?;
+        unsafe { mmap::advise(ptr.as_ptr(), len, libc::MADV_HUGEPAGE) };
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if is_mapped(layout) {
+            mmap::unmap(ptr, mapped_len(layout.size()).unwrap());
+        } else {
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !is_mapped(old_layout) {
+            if !is_mapped(new_layout) {
+                return Global.grow(ptr, old_layout, new_layout);
+            }
+            let new = self.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new.cast::<u8>().as_ptr(), old_layout.size());
+            Global.deallocate(ptr, old_layout);
+            return Ok(new);
+        }
+        let (old_len, new_len) = (
+            mapped_len(old_layout.size())?,
+            mapped_len(new_layout.size())?,
+        );
+        if new_len == old_len {
+            return Ok(NonNull::slice_from_raw_parts(ptr, old_len));
+        }
+        // A plain `MREMAP_MAYMOVE` may land on any page boundary, losing both the huge-page
+        // alignment and any larger `new_layout.align()`. Grow in place if possible, otherwise
+        // move the pages into a fresh huge-page aligned range.
+        let new = if mmap::remap_in_place(ptr, old_len, new_len).is_ok() {
+            ptr
+        } else {
+            let dst = mmap::map_aligned(new_len, HUGE_PAGE_SIZE)?;
+            if let Err(e) = mmap::remap_to(ptr, old_len, new_len, dst) {
+                mmap::unmap(dst, new_len);
+                return Err(e);
+            }
+            dst
+        };
+        mmap::advise(new.as_ptr(), new_len, libc::MADV_HUGEPAGE);
+        Ok(NonNull::slice_from_raw_parts(new, new_len))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !is_mapped(old_layout) {
+            return Global.shrink(ptr, old_layout, new_layout);
+        }
+        if !is_mapped(new_layout) {
+            let new = Global.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new.cast::<u8>().as_ptr(), new_layout.size());
+            self.deallocate(ptr, old_layout);
+            return Ok(new);
+        }
+        let (old_len, new_len) = (
+            mapped_len(old_layout.size())?,
+            mapped_len(new_layout.size())?,
+        );
+        if new_len < old_len {
+            mmap::unmap(
+                NonNull::new_unchecked(ptr.as_ptr().add(new_len)),
+                old_len - new_len,
+            );
+        }
+        let used = mmap::round_up(new_layout.size(), mmap::page_size()).unwrap();
+        mmap::advise(ptr.as_ptr().add(used), new_len - used, libc::MADV_DONTNEED);
+        Ok(NonNull::slice_from_raw_parts(ptr, new_len))
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use crate::Vec;
+
+    const PER_PAGE: usize = HUGE_PAGE_SIZE / 8;
+
+    #[test]
+    fn large_buffers_are_mapped() {
+        let mut v: Vec<u64, HugePageAlloc> = Vec::with_capacity_in(PER_PAGE, HugePageAlloc);
+        assert_eq!(v.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+        for i in 0..3 * PER_PAGE as u64 + 5 {
+            v.push(i);
+            assert_eq!(v.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+        }
+        assert_eq!(v[PER_PAGE], PER_PAGE as u64);
+
+        v.truncate(PER_PAGE + 1);
+        v.shrink_to_fit();
+        assert_eq!(v.capacity(), PER_PAGE + 1);
+        assert_eq!(v[PER_PAGE], PER_PAGE as u64);
+        v.push(7);
+
+        // Back below the threshold: moves to the global allocator
+        v.truncate(10);
+        v.shrink_to_fit();
+        assert!(v.iter().copied().eq(0..10));
+    }
+
+    #[test]
+    fn growth_keeps_large_alignment() {
+        #[repr(align(65536))]
+        struct Aligned(#[allow(dead_code)] u8);
+        let per_page = HUGE_PAGE_SIZE / 65536;
+        let mut v = Vec::with_capacity_in(per_page, HugePageAlloc);
+        // A mapping made now often sits right behind the buffer, forcing growth to move it
+        let blocker = mmap::map_aligned(HUGE_PAGE_SIZE, HUGE_PAGE_SIZE).unwrap();
+        for i in 0..4 * per_page {
+            v.push(Aligned(i as u8));
+            assert_eq!(v.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+        }
+        unsafe { mmap::unmap(blocker, HUGE_PAGE_SIZE) };
+    }
+
+    #[test]
+    fn small_buffers_use_global() {
+        let mut v = Vec::new_in(HugePageAlloc);
+        for i in 0..PER_PAGE as u64 * 2 {
+            v.push(i);
+        }
+        assert_eq!(v.iter().sum::<u64>(), (0..PER_PAGE as u64 * 2).sum::<u64>());
+    }
+}
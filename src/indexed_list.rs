@@ -0,0 +1,237 @@
+use crate::{MemoryReport, Vec};
+use core::ops::{Index, IndexMut};
+
+const CHUNK: usize = 256;
+
+// A sequence split into chunks of at most `2 * CHUNK` elements, with a Fenwick tree over the
+// chunk lengths. Locating a position is a Fenwick descent (O(log chunks)) followed by a shift
+// inside one chunk, so insertion and removal anywhere cost O(log n + CHUNK) instead of O(n).
+// Splitting or dropping a chunk rebuilds the tree, which happens at most once per `CHUNK` edits.
+pub struct IndexedList<T> {
+    chunks: Vec<Vec<T>>,
+    // 1-based Fenwick tree: `tree[i]` sums the lengths of chunks `i - (i & -i)..i`.
+    tree: Vec<usize>,
+    len: usize,
+}
+
+impl<T> IndexedList<T> {
+    pub fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            tree: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let (c, i) = self.locate(index);
+        Some(&self.chunks[c][i])
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let (c, i) = self.locate(index);
+        Some(&mut self.chunks[c][i])
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.insert(self.len, elem);
+    }
+
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(index <= self.len, "index out of bounds");
+        if self.chunks.is_empty() {
+            self.chunks.push(Vec::with_capacity(CHUNK));
+            self.rebuild();
+        }
+        // Appending goes to the last chunk rather than a fresh one at `chunks.len()`
+        let (c, i) = if index == self.len {
+            let c = self.chunks.len() - 1;
+            (c, self.chunks[c].len())
+        } else {
+            self.locate(index)
+        };
+        self.chunks[c].insert(i, elem);
+        self.len += 1;
+        if self.chunks[c].len() >= 2 * CHUNK {
+            let tail = self.chunks[c].take(CHUNK..);
+            self.chunks.insert(c + 1, tail);
+            self.rebuild();
+        } else {
+            self.add(c, 1);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "index out of bounds");
+        let (c, i) = self.locate(index);
+        let elem = self.chunks[c].remove(i);
+        self.len -= 1;
+        if self.chunks[c].is_empty() {
+            self.chunks.remove(c);
+            self.rebuild();
+        } else {
+            self.sub(c, 1);
+        }
+        elem
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            Some(self.remove(self.len - 1))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.chunks.iter().flat_map(|c| c.iter())
+    }
+
+    // Maps `index < len` to (chunk, offset within chunk).
+    fn locate(&self, mut index: usize) -> (usize, usize) {
+        let mut pos = 0;
+        let mut step = (self.tree.len() - 1).next_power_of_two();
+        while step > 0 {
+            let next = pos + step;
+            if next < self.tree.len() && self.tree[next] <= index {
+                index -= self.tree[next];
+                pos = next;
+            }
+            step /= 2;
+        }
+        (pos, index)
+    }
+
+    fn add(&mut self, chunk: usize, n: usize) {
+        let mut i = chunk + 1;
+        while i < self.tree.len() {
+            self.tree[i] += n;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn sub(&mut self, chunk: usize, n: usize) {
+        let mut i = chunk + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= n;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.tree.clear();
+        self.tree.push(0);
+        for c in self.chunks.iter() {
+            self.tree.push(c.len());
+        }
+        for i in 1..self.tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent < self.tree.len() {
+                self.tree[parent] += self.tree[i];
+            }
+        }
+    }
+}
+
+impl<T> Default for IndexedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for IndexedList<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for IndexedList<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+impl<T> core::iter::FromIterator<T> for IndexedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for x in iter {
+            list.push(x);
+        }
+        list
+    }
+}
+
+impl<T> MemoryReport for IndexedList<T> {
+    fn allocated_bytes(&self) -> usize {
+        let chunks: usize = self.chunks.iter().map(|c| c.allocated_bytes()).sum();
+        chunks + self.chunks.allocated_bytes() + self.tree.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        let chunks: usize = self.chunks.iter().map(|c| c.used_bytes()).sum();
+        chunks + self.chunks.used_bytes() + self.tree.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_std_vec() {
+        let mut list = IndexedList::new();
+        let mut model = std::vec::Vec::new();
+        let mut seed = 7u64;
+        for _ in 0..5000 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let r = (seed >> 33) as usize;
+            if model.is_empty() || !r.is_multiple_of(3) {
+                let at = r % (model.len() + 1);
+                list.insert(at, r);
+                model.insert(at, r);
+            } else {
+                let at = r % model.len();
+                assert_eq!(list.remove(at), model.remove(at));
+            }
+        }
+        assert_eq!(list.len(), model.len());
+        assert!(list.chunks.len() > 1);
+        assert!(list.iter().eq(model.iter()));
+        for (i, x) in model.iter().enumerate() {
+            assert_eq!(list[i], *x);
+        }
+        list[10] = 0;
+        assert_eq!(list.get(10), Some(&0));
+        assert_eq!(list.get(model.len()), None);
+    }
+
+    #[test]
+    fn drain_to_empty() {
+        let mut list: IndexedList<usize> = (0..1000).collect();
+        for i in (0..1000).rev() {
+            assert_eq!(list.pop(), Some(i));
+        }
+        assert!(list.is_empty());
+        assert!(list.chunks.is_empty());
+        list.insert(0, 5);
+        assert_eq!(list[0], 5);
+    }
+}
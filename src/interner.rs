@@ -0,0 +1,193 @@
+use crate::{JaggedVec, Vec};
+use core::convert::TryFrom;
+use core::hash::{BuildHasher, Hash};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// Maps hashes to symbols without keeping a second copy of each value: `heads` holds the newest
+// symbol per hash and `next` chains older symbols that share it, which the caller compares
+// against its own storage.
+struct Chains {
+    hasher: RandomState,
+    heads: HashMap<u64, Symbol>,
+    next: Vec<Option<Symbol>>,
+}
+
+impl Chains {
+    fn new() -> Self {
+        Self {
+            hasher: RandomState::new(),
+            heads: HashMap::new(),
+            next: Vec::new(),
+        }
+    }
+
+    fn find(&self, hash: u64, mut eq: impl FnMut(Symbol) -> bool) -> Option<Symbol> {
+        let mut cur = self.heads.get(&hash).copied();
+        while let Some(sym) = cur {
+            if eq(sym) {
+                return Some(sym);
+            }
+            cur = self.next[sym.index()];
+        }
+        None
+    }
+
+    fn insert(&mut self, hash: u64) -> Symbol {
+        let sym = Symbol(u32::try_from(self.next.len()).expect("too many interned values"));
+        self.next.push(self.heads.insert(hash, sym));
+        sym
+    }
+}
+
+pub struct Interner<T> {
+    values: Vec<T>,
+    chains: Chains,
+}
+
+impl<T: Hash + Eq> Interner<T> {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            chains: Chains::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn intern(&mut self, value: T) -> Symbol {
+        let hash = self.chains.hasher.hash_one(&value);
+        if let Some(sym) = self.get_hashed(hash, &value) {
+            return sym;
+        }
+        self.values.push(value);
+        self.chains.insert(hash)
+    }
+
+    pub fn get(&self, value: &T) -> Option<Symbol> {
+        self.get_hashed(self.chains.hasher.hash_one(value), value)
+    }
+
+    fn get_hashed(&self, hash: u64, value: &T) -> Option<Symbol> {
+        self.chains
+            .find(hash, |sym| self.values[sym.index()] == *value)
+    }
+
+    pub fn resolve(&self, sym: Symbol) -> &T {
+        &self.values[sym.index()]
+    }
+}
+
+impl<T: Hash + Eq> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Strings are stored back to back in a single byte buffer.
+pub struct StrInterner {
+    strings: JaggedVec<u8>,
+    chains: Chains,
+}
+
+impl StrInterner {
+    pub fn new() -> Self {
+        Self {
+            strings: JaggedVec::new(),
+            chains: Chains::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        let hash = self.chains.hasher.hash_one(s);
+        if let Some(sym) = self.get_hashed(hash, s) {
+            return sym;
+        }
+        self.strings.push_row(s.as_bytes());
+        self.chains.insert(hash)
+    }
+
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.get_hashed(self.chains.hasher.hash_one(s), s)
+    }
+
+    fn get_hashed(&self, hash: u64, s: &str) -> Option<Symbol> {
+        self.chains
+            .find(hash, |sym| &self.strings[sym.index()] == s.as_bytes())
+    }
+
+    pub fn resolve(&self, sym: Symbol) -> &str {
+        // Every row was copied from a `&str`.
+        unsafe { core::str::from_utf8_unchecked(&self.strings[sym.index()]) }
+    }
+}
+
+impl Default for StrInterner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_once() {
+        let mut i = Interner::new();
+        let a = i.intern((1, 2));
+        let b = i.intern((3, 4));
+        assert_eq!(i.intern((1, 2)), a);
+        assert_ne!(a, b);
+        assert_eq!(i.len(), 2);
+        assert_eq!(*i.resolve(b), (3, 4));
+        assert_eq!(i.get(&(5, 6)), None);
+    }
+
+    #[test]
+    fn strings() {
+        let mut i = StrInterner::new();
+        let words = ["let", "x", "=", "x", "+", "let", ""];
+        let syms: std::vec::Vec<_> = words.iter().map(|w| i.intern(w)).collect();
+        assert_eq!(i.len(), 5);
+        assert_eq!(syms[1], syms[3]);
+        assert_eq!(syms[0], syms[5]);
+        for (w, s) in words.iter().zip(&syms) {
+            assert_eq!(i.resolve(*s), *w);
+        }
+        assert_eq!(i.get("+"), Some(syms[4]));
+    }
+
+    #[test]
+    fn hash_collisions_are_chained() {
+        let mut chains = Chains::new();
+        let a = chains.insert(7);
+        let b = chains.insert(7);
+        assert_eq!(chains.find(7, |s| s == a), Some(a));
+        assert_eq!(chains.find(7, |s| s == b), Some(b));
+        assert_eq!(chains.find(8, |_| true), None);
+    }
+}
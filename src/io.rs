@@ -0,0 +1,170 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use std::io::{self, BorrowedBuf, IoSlice, Read, Write};
+
+const READ_CHUNK: usize = 8 * 1024;
+
+impl<A: Allocator> Vec<u8, A> {
+    // Appends everything `reader` yields until EOF and returns the number of bytes read. Reads
+    // go straight into the spare capacity through `read_buf`, so the buffer is never zeroed
+    // first; it grows by at least `READ_CHUNK` whenever it fills up.
+    pub fn read_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let start = self.len;
+        loop {
+            if self.len == self.buf.cap {
+                self.reserve(READ_CHUNK);
+            }
+            let mut buf = BorrowedBuf::from(self.spare_capacity_mut());
+            match reader.read_buf(buf.unfilled()) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+            let n = buf.len();
+            if n == 0 {
+                return Ok(self.len - start);
+            }
+            unsafe { self.set_len(self.len + n) };
+        }
+    }
+}
+
+impl<A: Allocator> Vec<u8, A> {
+    pub fn as_io_slice(&self) -> IoSlice<'_> {
+        IoSlice::new(self)
+    }
+}
+
+// Writes every buffer in order with as few `write_vectored` calls as the writer allows,
+// resuming after short writes instead of falling back to one write per buffer.
+pub fn write_all_vectored<W: Write + ?Sized>(writer: &mut W, bufs: &[Vec<u8>]) -> io::Result<()> {
+    let mut slices: Vec<IoSlice<'_>> = bufs.iter().map(Vec::as_io_slice).collect();
+    let mut rest = &mut slices[..];
+    IoSlice::advance_slices(&mut rest, 0);
+    while !rest.is_empty() {
+        match writer.write_vectored(rest) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => IoSlice::advance_slices(&mut rest, n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+pub fn read_to_vec<R: Read + ?Sized>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut v = Vec::new();
+    v.read_from(reader)?;
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hands out at most `step` bytes per call and fails with `Interrupted` every other call.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+        interrupt: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let n = self.step.min(out.len()).min(self.data.len());
+            out[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn reads_until_eof() {
+        let data: std::vec::Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+        let mut v = Vec::new();
+        v.push(7);
+        let mut r = Trickle {
+            data: &data,
+            step: 3000,
+            interrupt: false,
+        };
+        assert_eq!(v.read_from(&mut r).unwrap(), data.len());
+        assert_eq!(v[0], 7);
+        assert_eq!(&v[1..], &data[..]);
+
+        assert_eq!(&read_to_vec(&mut &b"hello"[..]).unwrap()[..], b"hello");
+    }
+
+    // Accepts at most `limit` bytes per call and counts the calls.
+    struct Capped {
+        out: std::vec::Vec<u8>,
+        limit: usize,
+        calls: usize,
+    }
+
+    impl Write for Capped {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            self.calls += 1;
+            let start = self.out.len();
+            for b in bufs {
+                let n = (self.limit - (self.out.len() - start)).min(b.len());
+                self.out.extend_from_slice(&b[..n]);
+            }
+            Ok(self.out.len() - start)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn vectored_writes() {
+        let bufs: std::vec::Vec<Vec<u8>> = (0..10u8)
+            .map(|i| {
+                let mut v = Vec::new();
+                v.extend_from_slice(&[i; 5]);
+                v
+            })
+            .collect();
+        let want: std::vec::Vec<u8> = (0..10u8).flat_map(|i| [i; 5]).collect();
+
+        let mut w = Capped {
+            out: std::vec::Vec::new(),
+            limit: usize::MAX,
+            calls: 0,
+        };
+        write_all_vectored(&mut w, &bufs).unwrap();
+        assert_eq!((w.out, w.calls), (want.clone(), 1));
+
+        let mut w = Capped {
+            out: std::vec::Vec::new(),
+            limit: 7,
+            calls: 0,
+        };
+        write_all_vectored(&mut w, &bufs).unwrap();
+        assert_eq!((w.out, w.calls), (want, 8));
+
+        assert_eq!(&*bufs[3].as_io_slice(), &[3; 5]);
+    }
+
+    #[test]
+    fn propagates_errors() {
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        let err = read_to_vec(&mut Broken).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{MemoryReport, Vec};
+use core::ops::Index;
+
+// Rows of varying length stored back to back in one buffer. `ends[i]` is the end of row `i` in
+// `data`; row `i` starts where row `i - 1` ends.
+pub struct JaggedVec<T> {
+    data: Vec<T>,
+    ends: Vec<usize>,
+}
+
+impl<T> JaggedVec<T> {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            ends: Vec::new(),
+        }
+    }
+
+    // Number of rows.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    // Total number of elements across all rows.
+    pub fn flat_len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn push_row_from<I: IntoIterator<Item = T>>(&mut self, row: I) {
+        self.data.extend(row);
+        self.ends.push(self.data.len());
+    }
+
+    pub fn pop_row(&mut self) -> bool {
+        match self.ends.pop() {
+            Some(_) => {
+                self.data.truncate(self.ends.last().copied().unwrap_or(0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn row(&self, index: usize) -> Option<&[T]> {
+        let end = *self.ends.get(index)?;
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        Some(&self.data[start..end])
+    }
+
+    pub fn row_mut(&mut self, index: usize) -> Option<&mut [T]> {
+        let end = *self.ends.get(index)?;
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        Some(&mut self.data[start..end])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        let mut start = 0;
+        self.ends.iter().map(move |&end| {
+            let row = &self.data[start..end];
+            start = end;
+            row
+        })
+    }
+
+    pub fn as_flat_slice(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T: Clone> JaggedVec<T> {
+    pub fn push_row(&mut self, row: &[T]) {
+        self.push_row_from(row.iter().cloned());
+    }
+}
+
+impl<T> Default for JaggedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<usize> for JaggedVec<T> {
+    type Output = [T];
+    fn index(&self, index: usize) -> &[T] {
+        self.row(index).expect("row index out of bounds")
+    }
+}
+
+impl<T> MemoryReport for JaggedVec<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.data.allocated_bytes() + self.ends.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.data.used_bytes() + self.ends.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows() {
+        let mut v = JaggedVec::new();
+        v.push_row(&[1, 2, 3]);
+        v.push_row(&[]);
+        v.push_row_from(4..6);
+        assert_eq!(v.len(), 3);
+        assert_eq!(v.flat_len(), 5);
+        assert_eq!(&v[0], &[1, 2, 3]);
+        assert!(v[1].is_empty());
+        assert_eq!(v.row(2), Some(&[4, 5][..]));
+        assert_eq!(v.row(3), None);
+        v.row_mut(2).unwrap()[0] = 40;
+        let lens: std::vec::Vec<_> = v.rows().map(|r| r.len()).collect();
+        assert_eq!(lens, [3, 0, 2]);
+        let ints = core::mem::size_of::<i32>() * 5 + core::mem::size_of::<usize>() * 3;
+        assert_eq!(v.used_bytes(), ints);
+        assert!(v.allocated_bytes() >= ints);
+
+        assert!(v.pop_row());
+        assert_eq!(v.as_flat_slice(), &[1, 2, 3]);
+        assert!(v.pop_row());
+        assert!(v.pop_row());
+        assert!(!v.pop_row());
+        assert_eq!(v.flat_len(), 0);
+    }
+}
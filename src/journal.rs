@@ -0,0 +1,208 @@
+use crate::{MemoryReport, Vec};
+use core::ops::Deref;
+
+// Each entry is the operation that reverts one edit; applying it yields the operation that
+// reverts it in turn, which is what moves between the undo and redo stacks.
+enum Op<T> {
+    Insert(usize, T),
+    Remove(usize),
+    Set(usize, T),
+}
+
+impl<T> Op<T> {
+    fn apply(self, vec: &mut Vec<T>) -> Op<T> {
+        match self {
+            Op::Insert(index, elem) => {
+                vec.insert(index, elem);
+                Op::Remove(index)
+            }
+            Op::Remove(index) => Op::Insert(index, vec.remove(index)),
+            Op::Set(index, elem) => Op::Set(index, core::mem::replace(&mut vec[index], elem)),
+        }
+    }
+}
+
+// A vector with undo/redo. Edits made between two `checkpoint` calls are undone together, and
+// only the most recent `limit` checkpoints are kept.
+pub struct JournaledVec<T> {
+    vec: Vec<T>,
+    pending: Vec<Op<T>>,
+    undo: Vec<Vec<Op<T>>>,
+    redo: Vec<Vec<Op<T>>>,
+    limit: usize,
+}
+
+impl<T: Clone> JournaledVec<T> {
+    pub fn new() -> Self {
+        Self::with_history_limit(usize::MAX)
+    }
+
+    pub fn with_history_limit(limit: usize) -> Self {
+        Self {
+            vec: Vec::new(),
+            pending: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    fn record(&mut self, op: Op<T>) {
+        self.redo.clear();
+        self.pending.push(op);
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.vec.push(elem);
+        self.record(Op::Remove(self.vec.len() - 1));
+    }
+
+    pub fn insert(&mut self, index: usize, elem: T) {
+        self.vec.insert(index, elem);
+        self.record(Op::Remove(index));
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let elem = self.vec.remove(index);
+        self.record(Op::Insert(index, elem.clone()));
+        elem
+    }
+
+    pub fn set(&mut self, index: usize, elem: T) -> T {
+        let old = core::mem::replace(&mut self.vec[index], elem);
+        self.record(Op::Set(index, old.clone()));
+        old
+    }
+
+    pub fn checkpoint(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let group = core::mem::take(&mut self.pending);
+        self.undo.push(group);
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+    }
+
+    // Reverts everything since the previous checkpoint. Returns false if there is nothing left.
+    pub fn undo(&mut self) -> bool {
+        self.checkpoint();
+        match self.undo.pop() {
+            Some(group) => {
+                let inverse = self.replay(group);
+                self.redo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.checkpoint();
+        match self.redo.pop() {
+            Some(group) => {
+                let inverse = self.replay(group);
+                self.undo.push(inverse);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Applies the ops newest first and returns their inverses in recording order.
+    fn replay(&mut self, mut group: Vec<Op<T>>) -> Vec<Op<T>> {
+        let mut inverse = Vec::with_capacity(group.len());
+        while let Some(op) = group.pop() {
+            inverse.push(op.apply(&mut self.vec));
+        }
+        inverse
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+impl<T: Clone> Default for JournaledVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for JournaledVec<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> MemoryReport for JournaledVec<T> {
+    // The current elements plus the undo and redo history.
+    fn allocated_bytes(&self) -> usize {
+        let history = self.undo.iter().chain(self.redo.iter());
+        let ops: usize = history.map(|ops| ops.allocated_bytes()).sum();
+        self.vec.allocated_bytes()
+            + self.pending.allocated_bytes()
+            + self.undo.allocated_bytes()
+            + self.redo.allocated_bytes()
+            + ops
+    }
+
+    fn used_bytes(&self) -> usize {
+        let history = self.undo.iter().chain(self.redo.iter());
+        let ops: usize = history.map(|ops| ops.used_bytes()).sum();
+        self.vec.used_bytes()
+            + self.pending.used_bytes()
+            + self.undo.used_bytes()
+            + self.redo.used_bytes()
+            + ops
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let mut v = JournaledVec::new();
+        v.push(1);
+        v.push(2);
+        v.checkpoint();
+        v.insert(0, 0);
+        v.set(2, 20);
+        v.checkpoint();
+        assert_eq!(v.remove(1), 1);
+        assert_eq!(&v[..], &[0, 20]);
+
+        assert!(v.undo());
+        assert_eq!(&v[..], &[0, 1, 20]);
+        assert!(v.undo());
+        assert_eq!(&v[..], &[1, 2]);
+        assert!(v.redo());
+        assert_eq!(&v[..], &[0, 1, 20]);
+        assert!(v.undo());
+        assert!(v.undo());
+        assert!(v.is_empty());
+        assert!(!v.undo());
+
+        assert!(v.redo());
+        v.push(3);
+        assert!(!v.redo());
+        assert_eq!(&v[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn history_limit() {
+        let mut v = JournaledVec::with_history_limit(2);
+        for i in 0..5 {
+            v.push(i);
+            v.checkpoint();
+        }
+        assert!(v.undo());
+        assert!(v.undo());
+        assert!(!v.undo());
+        assert_eq!(&v[..], &[0, 1, 2]);
+    }
+}
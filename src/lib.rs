@@ -1,66 +1,614 @@
+#![no_std]
 #![feature(ptr_internals)]
-#![feature(alloc_internals)]
-use std::alloc::{self, Layout};
-use std::iter::{DoubleEndedIterator, IntoIterator, Iterator};
-use std::marker::PhantomData;
-use std::mem;
-use std::ops::{Deref, DerefMut};
-use std::ptr::{self, Unique};
-
-struct RawVec<T> {
+#![feature(portable_simd)]
+#![feature(iter_advance_by)]
+#![feature(allocator_api)]
+#![feature(cfg_sanitize)]
+#![feature(min_specialization)]
+#![cfg_attr(
+    not(feature = "no_global_oom_handling"),
+    feature(extend_one, ptr_metadata, unsize)
+)]
+#![cfg_attr(
+    all(feature = "std", not(feature = "no_global_oom_handling")),
+    feature(core_io_borrowed_buf, read_buf)
+)]
+#![allow(internal_features)]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use alloc::alloc::{Global, Layout};
+use alloc::boxed::Box;
+#[cfg(not(feature = "no_global_oom_handling"))]
+use alloc::rc::Rc;
+#[cfg(not(feature = "no_global_oom_handling"))]
+use alloc::sync::Arc;
+use core::alloc::Allocator;
+use core::fmt;
+use core::iter::{DoubleEndedIterator, IntoIterator, Iterator};
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::num::NonZeroUsize;
+use core::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
+use core::ptr::{self, NonNull, Unique};
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+// With the `strict` feature, checks an invariant that the unsafe code below relies on and
+// aborts on violation; otherwise compiles to nothing.
+macro_rules! strict_assert {
+    ($cond:expr, $($arg:tt)+) => {
+        #[cfg(feature = "strict")]
+        {
+            if !$cond {
+                $crate::strict_violation(format_args!($($arg)+));
+            }
+        }
+    };
+}
+
+// Replays a mutation on the shadow copy (feature `shadow`), or marks it stale when the change
+// can't be replayed.
+macro_rules! shadow {
+    ($vec:expr, stale) => {
+        #[cfg(feature = "shadow")]
+        $vec.shadow_stale();
+    };
+    ($vec:expr, $op:literal, $f:expr) => {
+        #[cfg(feature = "shadow")]
+        $vec.shadow_replay($op, $f);
+    };
+}
+
+#[cfg(feature = "strict")]
+#[cold]
+fn strict_violation(args: fmt::Arguments<'_>) -> ! {
+    // Tests panic instead so that violations can be checked with `should_panic`
+    #[cfg(all(feature = "std", not(test)))]
+    {
+        std::eprintln!("rust_vec: invariant violated: {}", args);
+        std::process::abort()
+    }
+    #[cfg(not(all(feature = "std", not(test))))]
+    panic!("rust_vec: invariant violated: {}", args)
+}
+
+#[cfg(all(feature = "strict", not(feature = "no_global_oom_handling")))]
+fn overlaps<T>(a: *const T, b: *const T, count: usize) -> bool {
+    let bytes = count * mem::size_of::<T>();
+    let (a, b) = (a as usize, b as usize);
+    bytes != 0 && a < b + bytes && b < a + bytes
+}
+
+// Buffers at least this large are reported at WARN rather than DEBUG.
+#[cfg(feature = "tracing")]
+const LARGE_ALLOCATION_BYTES: usize = 16 << 20;
+
+#[cfg(feature = "tracing")]
+fn trace_realloc<T>(event: &'static str, old_cap: usize, new_cap: usize) {
+    let bytes = new_cap * mem::size_of::<T>();
+    let elem = core::any::type_name::<T>();
+    if bytes >= LARGE_ALLOCATION_BYTES {
+        tracing::warn!(target: "rust_vec", event, elem, old_cap, new_cap, bytes, "large allocation");
+    } else {
+        tracing::debug!(target: "rust_vec", event, elem, old_cap, new_cap, bytes);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+fn trace_realloc<T>(_event: &'static str, _old_cap: usize, _new_cap: usize) {}
+
+#[cfg(all(feature = "rkyv", not(feature = "no_global_oom_handling")))]
+mod archive;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod arrays;
+#[cfg(all(feature = "arrow", not(feature = "no_global_oom_handling")))]
+mod arrow;
+mod asan;
+#[cfg(all(feature = "borsh", not(feature = "no_global_oom_handling")))]
+mod borsh_io;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod bounded;
+#[cfg(all(feature = "bytes", not(feature = "no_global_oom_handling")))]
+mod buf;
+#[cfg(feature = "memchr")]
+mod bytesearch;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod chunk_by;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod compressed;
+mod ct;
+mod cursor;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod diff;
+mod display;
+#[cfg(all(feature = "elastic", target_os = "linux"))]
+mod elastic;
+#[cfg(all(feature = "encoding", not(feature = "no_global_oom_handling")))]
+mod encoding;
+mod fault_alloc;
+#[cfg(all(feature = "ffi", not(feature = "no_global_oom_handling")))]
+pub mod ffi;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod grid;
+#[cfg(all(feature = "guard_pages", target_os = "linux"))]
+mod guard;
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+mod hugepage;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod indexed_list;
+#[cfg(all(feature = "std", not(feature = "no_global_oom_handling")))]
+mod interner;
+#[cfg(all(feature = "std", not(feature = "no_global_oom_handling")))]
+pub mod io;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod jagged;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod journal;
+#[cfg(feature = "metrics")]
+mod meter;
+#[cfg(all(
+    any(
+        feature = "elastic",
+        feature = "guard_pages",
+        feature = "hugepages",
+        feature = "numa"
+    ),
+    target_os = "linux"
+))]
+mod mmap;
+#[cfg(all(feature = "model", not(feature = "no_global_oom_handling")))]
+pub mod model;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod nullable;
+#[cfg(all(
+    feature = "numa",
+    target_os = "linux",
+    not(feature = "no_global_oom_handling")
+))]
+pub mod numa;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod observable;
+#[cfg(all(feature = "rayon", not(feature = "no_global_oom_handling")))]
+mod par;
+#[cfg(feature = "bytemuck")]
+pub mod pod;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod pool;
+mod pool_alloc;
+#[cfg(all(kani, not(feature = "no_global_oom_handling")))]
+mod proofs;
+#[cfg(all(feature = "python", not(feature = "no_global_oom_handling")))]
+pub mod python;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod radix;
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod ring;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod segmented;
+#[cfg(all(feature = "serde", not(feature = "no_global_oom_handling")))]
+pub mod serde_bytes;
+#[cfg(all(feature = "serde", not(feature = "no_global_oom_handling")))]
+mod serde_vec;
+#[cfg(feature = "shadow")]
+mod shadow;
+mod simd;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod sort;
+#[cfg(all(
+    feature = "bytemuck",
+    feature = "std",
+    not(feature = "no_global_oom_handling")
+))]
+mod spill;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub mod spsc;
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod unsized_vec;
+mod utf8;
+mod windows;
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use arrays::IntoArrays;
+#[cfg(all(feature = "arrow", not(feature = "no_global_oom_handling")))]
+pub use arrow::{ArrowAlloc, ARROW_ALIGN};
+#[cfg(all(feature = "borsh", not(feature = "no_global_oom_handling")))]
+pub use borsh_io::BoundedLen;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use bounded::BoundedVec;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use chunk_by::IntoChunkBy;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use compressed::{CompressInt, CompressedVec};
+pub use cursor::CursorMut;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use diff::DiffOp;
+pub use display::VecDisplay;
+#[cfg(all(feature = "elastic", target_os = "linux"))]
+pub use elastic::{ElasticAlloc, ElasticVec};
+#[cfg(all(feature = "encoding", not(feature = "no_global_oom_handling")))]
+pub use encoding::DecodeError;
+pub use fault_alloc::FaultyAlloc;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use grid::Grid;
+#[cfg(all(feature = "guard_pages", target_os = "linux"))]
+pub use guard::GuardPageAlloc;
+#[cfg(all(feature = "hugepages", target_os = "linux"))]
+pub use hugepage::{HugePageAlloc, HUGE_PAGE_SIZE};
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use indexed_list::IndexedList;
+#[cfg(all(feature = "std", not(feature = "no_global_oom_handling")))]
+pub use interner::{Interner, StrInterner, Symbol};
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use jagged::JaggedVec;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use journal::JournaledVec;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use nullable::NullableVec;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use observable::{Change, ObservableVec};
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use pool::{BufferPool, PooledVec};
+pub use pool_alloc::{PoolAlloc, POOL_ALIGN};
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use radix::RadixKey;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use ring::RingBuffer;
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use segmented::{SegmentedVec, Snapshot};
+pub use simd::SimdFind;
+#[cfg(all(
+    feature = "bytemuck",
+    feature = "std",
+    not(feature = "no_global_oom_handling")
+))]
+pub use spill::{SpillIter, SpillVec};
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub use unsized_vec::UnsizedVec;
+pub use windows::WindowsMut;
+
+struct RawVec<T, A: Allocator = Global> {
     ptr: Unique<T>,
     cap: usize,
+    // Set by `Vec::lock_capacity`: growing fails and shrinking does nothing
+    locked: bool,
+    // Set by `Vec::set_max_capacity`; `usize::MAX` when unlimited
+    max_cap: usize,
+    alloc: A,
+    #[cfg(feature = "metrics")]
+    family: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TryReserveError {
+    CapacityOverflow,
+    AllocError { layout: Layout },
+    CapacityLocked,
+}
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryReserveError::CapacityOverflow => f.write_str("capacity overflow"),
+            TryReserveError::AllocError { layout } => {
+                write!(f, "memory allocation of {} bytes failed", layout.size())
+            }
+            TryReserveError::CapacityLocked => f.write_str("capacity is locked"),
+        }
+    }
+}
+
+impl core::error::Error for TryReserveError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError;
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("capacity overflow")
+    }
+}
+
+impl core::error::Error for CapacityError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GetDisjointMutError {
+    IndexOutOfBounds,
+    OverlappingIndices,
+}
+
+impl fmt::Display for GetDisjointMutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetDisjointMutError::IndexOutOfBounds => f.write_str("index out of bounds"),
+            GetDisjointMutError::OverlappingIndices => f.write_str("overlapping indices"),
+        }
+    }
+}
+
+impl core::error::Error for GetDisjointMutError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthError {
+    TooFew { expected: usize, actual: usize },
+    TooMany { expected: usize },
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthError::TooFew { expected, actual } => {
+                write!(f, "expected {} elements, got {}", expected, actual)
+            }
+            LengthError::TooMany { expected } => {
+                write!(f, "expected {} elements, got more", expected)
+            }
+        }
+    }
+}
+
+impl core::error::Error for LengthError {}
+
+impl From<CapacityError> for TryReserveError {
+    fn from(_: CapacityError) -> Self {
+        TryReserveError::CapacityOverflow
+    }
+}
+
+// Smallest capacity an amortized reservation allocates. Growing tiny elements one or two at a
+// time is mostly allocator overhead, while big elements shouldn't over-reserve.
+const fn min_non_zero_cap<T>() -> usize {
+    if mem::size_of::<T>() == 1 {
+        8
+    } else if mem::size_of::<T>() <= 1024 {
+        4
+    } else {
+        1
+    }
+}
+
+// All capacity arithmetic goes through here: the capacity needed for `len + additional`
+// elements (at least doubling `cap` when `amortized`, but never past `max_cap`) and the layout
+// of that buffer.
+fn checked_capacity<T>(
+    cap: usize,
+    len: usize,
+    additional: usize,
+    amortized: bool,
+    max_cap: usize,
+) -> Result<(usize, Layout), CapacityError> {
+    let required = len.checked_add(additional).ok_or(CapacityError)?;
+    if required > max_cap {
+        return Err(CapacityError);
+    }
+    let new_cap = if amortized {
+        required
+            .max(cap.saturating_mul(2))
+            .max(min_non_zero_cap::<T>())
+            .min(max_cap)
+    } else {
+        required
+    };
+    // Since LLVM doesn't have unsigned integer type, the allowed maximum usize is isize:MAX.
+    // `Layout::array` rejects anything larger.
+    let layout = Layout::array::<T>(new_cap).map_err(|_| CapacityError)?;
+    Ok((new_cap, layout))
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+fn handle_reserve_error(e: TryReserveError) -> ! {
+    match e {
+        TryReserveError::CapacityOverflow => panic!("capacity overflow"),
+        TryReserveError::AllocError { layout } => alloc::alloc::handle_alloc_error(layout),
+        TryReserveError::CapacityLocked => panic!("capacity is locked"),
+    }
+}
+
+// Called with the layout of a failed allocation. Returning `true` asks the grow path to retry,
+// e.g. after the hook dropped some caches; `false` gives up and reports the failure.
+pub type AllocErrorHook = fn(Layout) -> bool;
+
+static ALLOC_ERROR_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+pub fn set_alloc_error_hook(hook: Option<AllocErrorHook>) {
+    let hook = hook.map_or(ptr::null_mut(), |f| f as *mut ());
+    ALLOC_ERROR_HOOK.store(hook, Ordering::Release);
+}
+
+fn call_alloc_error_hook(layout: Layout) -> bool {
+    let hook = ALLOC_ERROR_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        false
+    } else {
+        let hook: AllocErrorHook = unsafe { mem::transmute(hook) };
+        hook(layout)
+    }
 }
 
 impl<T> RawVec<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+}
+
+impl<T, A: Allocator> RawVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         Self {
             ptr: Unique::dangling(),
             cap: if mem::size_of::<T>() == 0 { !0 } else { 0 },
+            locked: false,
+            max_cap: usize::MAX,
+            alloc,
+            #[cfg(feature = "metrics")]
+            family: meter::DEFAULT_FAMILY,
         }
     }
 
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut buf = Self::new_in(alloc);
+        buf.reserve(0, cap, false);
+        buf
+    }
+
+    fn as_non_null(&self) -> NonNull<u8> {
+        NonNull::from(self.ptr).cast()
+    }
+
+    // Clears the ASAN marks of `Vec` before the buffer is handed back to the allocator.
+    fn unpoison(&self) {
+        if self.cap != 0 {
+            asan::unpoison(self.ptr.as_ptr(), self.cap);
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    #[cold]
+    #[inline(never)]
     fn grow(&mut self) {
-        assert!(mem::size_of::<T>() != 0, "capacity overflow");
-        unsafe {
-            let layout = Layout::new::<T>();
-            let (new_cap, new_ptr) = if self.cap == 0 {
-                (1, alloc::alloc(layout))
-            } else {
-                let new_cap = self.cap * 2;
-                let new_layout = Layout::array::<T>(new_cap).unwrap();
+        self.reserve(self.cap, 1, true);
+    }
 
-                if new_layout.size() >= std::isize::MAX as usize {
-                    // Since LLVM doesn't have unsigned integer type, the allowed maximum usize is isize:MAX
-                    panic!("capacity overflow");
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    fn reserve(&mut self, len: usize, additional: usize, amortized: bool) {
+        if let Err(e) = self.try_reserve(len, additional, amortized) {
+            handle_reserve_error(e);
+        }
+    }
+
+    fn try_reserve(
+        &mut self,
+        len: usize,
+        additional: usize,
+        mut amortized: bool,
+    ) -> Result<(), TryReserveError> {
+        if self.cap - len >= additional {
+            return Ok(());
+        }
+        if mem::size_of::<T>() == 0 {
+            // Zero-sized elements already have the maximum capacity
+            return Err(TryReserveError::CapacityOverflow);
+        }
+        if self.locked {
+            return Err(TryReserveError::CapacityLocked);
+        }
+        let (mut new_cap, mut new_layout) =
+            checked_capacity::<T>(self.cap, len, additional, amortized, self.max_cap)?;
+        unsafe {
+            let new_ptr = loop {
+                let new_ptr = if self.cap == 0 {
+                    self.alloc.allocate(new_layout)
+                } else {
+                    self.unpoison();
+                    let old_layout = Layout::array::<T>(self.cap).unwrap();
+                    self.alloc.grow(self.as_non_null(), old_layout, new_layout)
+                };
+                if let Ok(new_ptr) = new_ptr {
+                    break new_ptr.cast::<u8>().as_ptr();
                 }
-                let new_ptr =
-                    alloc::realloc(self.ptr.as_ptr() as *mut _, layout, new_layout.size());
-                if new_ptr.is_null() {
-                    alloc::rust_oom(layout);
+                // The doubled size may be past what the allocator can give (e.g. the end of an
+                // `ElasticAlloc` reservation) while the exact size still fits
+                if amortized && new_cap > len + additional {
+                    amortized = false;
+                    (new_cap, new_layout) =
+                        checked_capacity::<T>(self.cap, len, additional, false, self.max_cap)?;
+                    continue;
+                }
+                // A failed realloc leaves the old buffer untouched, so retrying is safe
+                if !call_alloc_error_hook(new_layout) {
+                    return Err(TryReserveError::AllocError { layout: new_layout });
                 }
-                (new_cap, new_ptr)
             };
-            self.ptr = Unique::new(new_ptr as *mut T).unwrap();
+            self.note_realloc("grow", new_cap);
+            self.ptr = Unique::new_unchecked(new_ptr as *mut T);
             self.cap = new_cap;
         }
+        Ok(())
     }
 }
 
-impl<T> Drop for RawVec<T> {
+impl<T, A: Allocator> RawVec<T, A> {
+    // Called before `cap` is updated.
+    fn note_realloc(&self, event: &'static str, new_cap: usize) {
+        trace_realloc::<T>(event, self.cap, new_cap);
+        #[cfg(feature = "metrics")]
+        meter::record_realloc::<T>(self.family, self.cap, new_cap);
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    fn shrink_to(&mut self, new_cap: usize) {
+        if let Err(e) = self.try_shrink_to(new_cap) {
+            handle_reserve_error(e);
+        }
+    }
+
+    fn try_shrink_to(&mut self, new_cap: usize) -> Result<(), TryReserveError> {
+        if mem::size_of::<T>() == 0 || new_cap >= self.cap || self.locked {
+            return Ok(());
+        }
+        self.unpoison();
+        unsafe {
+            let old_layout = Layout::array::<T>(self.cap).unwrap();
+            if new_cap == 0 {
+                self.alloc.deallocate(self.as_non_null(), old_layout);
+                self.ptr = Unique::dangling();
+            } else {
+                let new_layout = Layout::array::<T>(new_cap).unwrap();
+                let new_ptr = self
+                    .alloc
+                    .shrink(self.as_non_null(), old_layout, new_layout)
+                    .map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+                self.ptr = Unique::new_unchecked(new_ptr.cast::<T>().as_ptr());
+            }
+            self.note_realloc("shrink", new_cap);
+            self.cap = new_cap;
+        }
+        Ok(())
+    }
+}
+
+impl<T, A: Allocator> Drop for RawVec<T, A> {
     fn drop(&mut self) {
         if self.cap != 0 && mem::size_of::<T>() != 0 {
             let layout = Layout::array::<T>(self.cap).unwrap();
+            self.unpoison();
             unsafe {
-                alloc::dealloc(self.ptr.as_ptr() as *mut _, layout);
+                self.alloc.deallocate(self.as_non_null(), layout);
             }
         }
     }
 }
 
-struct Vec<T> {
-    buf: RawVec<T>,
+pub struct Vec<T, A: Allocator = Global> {
+    buf: RawVec<T, A>,
     len: usize,
+    shrink: Option<ShrinkPolicy>,
+    poisoned: bool,
+    #[cfg(feature = "shadow")]
+    shadow: Option<shadow::Shadow<T>>,
+}
+
+// Halve the capacity whenever the length drops below `1 / divisor` of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShrinkPolicy {
+    divisor: NonZeroUsize,
+}
+
+impl ShrinkPolicy {
+    pub fn below_fraction(divisor: usize) -> Self {
+        assert!(divisor >= 2, "shrink divisor must be at least 2");
+        Self {
+            divisor: NonZeroUsize::new(divisor).unwrap(),
+        }
+    }
 }
 
 impl<T> Vec<T> {
@@ -68,35 +616,444 @@ impl<T> Vec<T> {
         Self {
             buf: RawVec::new(),
             len: 0,
+            shrink: None,
+            poisoned: false,
+            #[cfg(feature = "shadow")]
+            shadow: None,
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn with_capacity(cap: usize) -> Self {
+        let v = Self {
+            buf: RawVec::with_capacity(cap),
+            len: 0,
+            shrink: None,
+            poisoned: false,
+            #[cfg(feature = "shadow")]
+            shadow: None,
+        };
+        v.poison_spare();
+        v
+    }
+
+    // Like `with_capacity`, but reports an oversized or failed allocation instead of panicking
+    // or aborting.
+    pub fn try_with_capacity(cap: usize) -> Result<Self, TryReserveError> {
+        let mut v = Self::new();
+        v.buf.try_reserve(0, cap, false)?;
+        v.poison_spare();
+        Ok(v)
+    }
+
+    // Allocates once and sets element `i` to `f(i)`. If `f` panics, the elements already built
+    // are dropped along with the partial vector.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn from_fn<F: FnMut(usize) -> T>(n: usize, mut f: F) -> Self {
+        let mut v = Self::with_capacity(n);
+        for i in 0..n {
+            unsafe { v.push_unchecked(f(i)) };
+        }
+        v
+    }
+
+    // Allocates exactly `n` and fails unless `iter` yields exactly `n` elements, whatever its
+    // size hint claims. One element past `n` is taken from `iter` to detect the excess.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn from_iter_exact<I: IntoIterator<Item = T>>(
+        n: usize,
+        iter: I,
+    ) -> Result<Self, LengthError> {
+        let mut iter = iter.into_iter();
+        let mut v = Self::with_capacity(n);
+        // Bounded by `n` as well, since zero-sized types have unlimited capacity
+        let actual = v.extend_within_capacity(iter.by_ref().take(n));
+        if actual < n {
+            return Err(LengthError::TooFew {
+                expected: n,
+                actual,
+            });
+        }
+        if iter.next().is_some() {
+            return Err(LengthError::TooMany { expected: n });
+        }
+        Ok(v)
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` and `capacity` must come from a vector of this crate (e.g. via `into_raw_parts`),
+    /// and the first `length` elements must be initialized.
+    pub unsafe fn from_raw_parts(ptr: *mut T, length: usize, capacity: usize) -> Self {
+        strict_assert!(
+            length <= capacity,
+            "from_raw_parts length {} exceeds capacity {}",
+            length,
+            capacity
+        );
+        strict_assert!(
+            !ptr.is_null() && ptr.is_aligned(),
+            "from_raw_parts pointer {:p} is null or misaligned",
+            ptr
+        );
+        let v = Self {
+            buf: RawVec {
+                ptr: unsafe { Unique::new_unchecked(ptr) },
+                cap: capacity,
+                locked: false,
+                max_cap: usize::MAX,
+                alloc: Global,
+                #[cfg(feature = "metrics")]
+                family: meter::DEFAULT_FAMILY,
+            },
+            len: length,
+            shrink: None,
+            poisoned: false,
+            #[cfg(feature = "shadow")]
+            shadow: None,
+        };
+        v.poison_spare();
+        v
+    }
+
+    // The spare capacity is unpoisoned on the way out; the caller may fill it by hand.
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        #[cfg_attr(not(feature = "shadow"), allow(unused_mut))]
+        let mut me = mem::ManuallyDrop::new(self);
+        #[cfg(feature = "shadow")]
+        drop(me.shadow.take());
+        me.asan_unpoison(me.len..me.buf.cap);
+        (me.buf.ptr.as_ptr(), me.len, me.buf.cap)
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            buf: RawVec::new_in(alloc),
+            len: 0,
+            shrink: None,
+            poisoned: false,
+            #[cfg(feature = "shadow")]
+            shadow: None,
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let v = Self {
+            buf: RawVec::with_capacity_in(cap, alloc),
+            len: 0,
+            shrink: None,
+            poisoned: false,
+            #[cfg(feature = "shadow")]
+            shadow: None,
+        };
+        v.poison_spare();
+        v
+    }
+
+    pub fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut v = Self::new_in(alloc);
+        v.buf.try_reserve(0, cap, false)?;
+        v.poison_spare();
+        Ok(v)
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.buf.alloc
+    }
+
+    /// # Safety
+    ///
+    /// `new_len` must not exceed the capacity and the first `new_len` elements must be
+    /// initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        strict_assert!(
+            new_len <= self.buf.cap,
+            "set_len({}) exceeds capacity {}",
+            new_len,
+            self.buf.cap
+        );
+        self.asan_unpoison(self.len..new_len);
+        self.len = new_len;
+        self.poison_spare();
+        shadow!(self, stale);
+    }
+
+    // Hands out the uninitialized part of the buffer, to be filled and then committed with
+    // `set_len`.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let spare = self.buf.cap - self.len;
+        self.asan_unpoison(self.len..self.buf.cap);
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.buf.ptr.as_ptr().add(self.len) as *mut MaybeUninit<T>,
+                spare,
+            )
+        }
+    }
+
+    // Under ASAN, `[len, cap)` stays poisoned between calls; see `asan.rs`. Each mutation
+    // unpoisons the slots it is about to fill and poisons the ones it vacates.
+    fn poison_spare(&self) {
+        self.asan_poison(self.len..self.buf.cap);
+    }
+
+    fn asan_poison(&self, range: Range<usize>) {
+        if range.start < range.end {
+            asan::poison(self.buf.ptr.as_ptr().wrapping_add(range.start), range.len());
+        }
+    }
+
+    fn asan_unpoison(&self, range: Range<usize>) {
+        if range.start < range.end {
+            asan::unpoison(self.buf.ptr.as_ptr().wrapping_add(range.start), range.len());
+        }
+    }
+
+    // Set when a user callback or `Drop` panicked in the middle of a mutation; every later
+    // access panics until `clear_poison` is called.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    pub fn clear_poison(&mut self) {
+        self.poisoned = false;
+    }
+
+    #[inline]
+    fn check_poison(&self) {
+        if self.poisoned {
+            poisoned_panic();
+        }
+    }
+
+    // Forbids any reallocation until `unlock_capacity`: operations that would grow the buffer
+    // fail with `TryReserveError::CapacityLocked` (the infallible ones panic), and shrinking
+    // leaves the buffer alone. Reserve what the hot path needs first, then lock.
+    pub fn lock_capacity(&mut self) {
+        self.buf.locked = true;
+    }
+
+    pub fn unlock_capacity(&mut self) {
+        self.buf.locked = false;
+    }
+
+    pub fn is_capacity_locked(&self) -> bool {
+        self.buf.locked
+    }
+
+    // Caps how far the buffer may grow, in elements: reserving past `max` fails with
+    // `TryReserveError::CapacityOverflow` (the infallible operations panic), and amortized growth
+    // stops doubling at `max`. Useful to keep within a 32-bit address space, e.g. on wasm32. A
+    // buffer that is already larger is left alone.
+    pub fn with_max_capacity(mut self, max: usize) -> Self {
+        self.set_max_capacity(Some(max));
+        self
+    }
+
+    pub fn set_max_capacity(&mut self, max: Option<usize>) {
+        self.buf.max_cap = max.unwrap_or(usize::MAX);
+    }
+
+    pub fn max_capacity(&self) -> Option<usize> {
+        Some(self.buf.max_cap).filter(|&max| max != usize::MAX)
+    }
+
+    pub fn with_shrink_policy(mut self, policy: ShrinkPolicy) -> Self {
+        self.shrink = Some(policy);
+        self
+    }
+
+    pub fn set_shrink_policy(&mut self, policy: Option<ShrinkPolicy>) {
+        self.shrink = policy;
+    }
+
+    fn maybe_shrink(&mut self) {
+        if let Some(policy) = self.shrink {
+            let mut cap = self.buf.cap;
+            while self.len < cap / policy.divisor.get() {
+                cap /= 2;
+            }
+            // Shrinking is only an optimization, so a failed reallocation keeps the old buffer
+            if cap < self.buf.cap {
+                let _ = self.buf.try_shrink_to(cap);
+                self.poison_spare();
+            }
         }
     }
 
+    #[inline]
     pub fn capacity(&self) -> usize {
         self.buf.cap
     }
 
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.check_poison();
+        let cap = self.buf.cap;
+        let res = self.buf.try_reserve(self.len, additional, true);
+        self.repoison_after_realloc(cap);
+        res
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn reserve(&mut self, additional: usize) {
+        self.check_poison();
+        let cap = self.buf.cap;
+        self.buf.reserve(self.len, additional, true);
+        self.repoison_after_realloc(cap);
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn shrink_to_fit(&mut self) {
+        self.check_poison();
+        self.buf.shrink_to(self.len);
+        self.poison_spare();
+    }
+
+    // Kept out of line so that `push` and `insert` inline down to a capacity check and a write.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    #[cold]
+    #[inline(never)]
+    fn grow(&mut self) {
+        self.buf.grow();
+        self.poison_spare();
+    }
+
+    // The buffer comes back from a reallocation fully unpoisoned.
+    fn repoison_after_realloc(&self, old_cap: usize) {
+        if self.buf.cap != old_cap {
+            self.poison_spare();
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    #[inline]
     pub fn push(&mut self, elem: T) {
+        self.check_poison();
         if self.buf.cap == self.len {
-            self.buf.grow()
+            self.grow()
+        }
+        self.asan_unpoison(self.len..self.len + 1);
+        unsafe { ptr::write(self.buf.ptr.as_ptr().add(self.len), elem) };
+        self.len += 1;
+        shadow!(self, "push", |s, ours| s.push(&ours[ours.len() - 1]));
+    }
+
+    // On failure `elem` is handed back along with the error.
+    pub fn try_push(&mut self, elem: T) -> Result<(), (TryReserveError, T)> {
+        self.check_poison();
+        if let Err(e) = self.try_reserve(1) {
+            return Err((e, elem));
         }
-        unsafe { ptr::write(self.buf.ptr.as_ptr().offset(self.len as isize), elem) };
+        self.asan_unpoison(self.len..self.len + 1);
+        unsafe { ptr::write(self.buf.ptr.as_ptr().add(self.len), elem) };
         self.len += 1;
+        shadow!(self, "try_push", |s, ours| s.push(&ours[ours.len() - 1]));
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// `self.len()` must be less than `self.capacity()`, e.g. after a `reserve` covering every
+    /// element about to be pushed.
+    #[inline]
+    pub unsafe fn push_unchecked(&mut self, elem: T) {
+        self.check_poison();
+        strict_assert!(
+            self.len < self.buf.cap,
+            "push_unchecked with length {} at capacity",
+            self.len
+        );
+        self.asan_unpoison(self.len..self.len + 1);
+        unsafe { ptr::write(self.buf.ptr.as_ptr().add(self.len), elem) };
+        self.len += 1;
+        shadow!(self, "push_unchecked", |s, ours| s
+            .push(&ours[ours.len() - 1]));
+    }
+
+    // Appends from `iter` until it runs out or the spare capacity is used up, and returns how
+    // many elements were appended. Nothing is taken from `iter` once the vector is full, so
+    // passing `iter.by_ref()` leaves the rest for the caller.
+    pub fn extend_within_capacity<I: IntoIterator<Item = T>>(&mut self, iter: I) -> usize {
+        self.check_poison();
+        let start = self.len;
+        let mut iter = iter.into_iter();
+        while self.len < self.buf.cap {
+            let Some(elem) = iter.next() else { break };
+            self.asan_unpoison(self.len..self.len + 1);
+            unsafe { ptr::write(self.buf.ptr.as_ptr().add(self.len), elem) };
+            self.len += 1;
+        }
+        shadow!(self, stale);
+        self.len - start
+    }
+
+    // Fallible `extend`. If growing fails, the elements appended so far stay in the vector and
+    // the first one that didn't fit is handed back with the error; pass `iter.by_ref()` to keep
+    // the rest.
+    pub fn try_extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), (TryReserveError, T)> {
+        let mut iter = iter.into_iter().peekable();
+        loop {
+            self.extend_within_capacity(&mut iter);
+            if iter.peek().is_none() {
+                return Ok(());
+            }
+            if let Err(e) = self.try_reserve(iter.size_hint().0) {
+                return Err((e, iter.next().unwrap()));
+            }
+        }
     }
 
+    #[inline]
     pub fn pop(&mut self) -> Option<T> {
+        self.check_poison();
         if self.len == 0 {
             None
         } else {
             self.len -= 1;
-            unsafe { Some(ptr::read(self.buf.ptr.as_ptr().add(self.len))) }
+            let elem = unsafe { ptr::read(self.buf.ptr.as_ptr().add(self.len)) };
+            self.asan_poison(self.len..self.len + 1);
+            self.maybe_shrink();
+            shadow!(self, "pop", |s, _| s.take(&elem, |v| v.pop().unwrap()));
+            Some(elem)
         }
     }
 
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    #[inline]
     pub fn insert(&mut self, index: usize, elem: T) {
+        self.check_poison();
         assert!(index <= self.len, "index out of bounds");
         if self.len == self.buf.cap {
-            self.buf.grow()
+            self.grow()
+        }
+        unsafe { self.insert_within_capacity(index, elem) }
+    }
+
+    pub fn try_insert(&mut self, index: usize, elem: T) -> Result<(), (TryReserveError, T)> {
+        self.check_poison();
+        assert!(index <= self.len, "index out of bounds");
+        if let Err(e) = self.try_reserve(1) {
+            return Err((e, elem));
         }
+        unsafe { self.insert_within_capacity(index, elem) };
+        Ok(())
+    }
+
+    unsafe fn insert_within_capacity(&mut self, index: usize, elem: T) {
+        strict_assert!(
+            self.len < self.buf.cap && index <= self.len,
+            "insert at {} with length {} and capacity {}",
+            index,
+            self.len,
+            self.buf.cap
+        );
+        self.asan_unpoison(self.len..self.len + 1);
         let p = self.buf.ptr.as_ptr();
         unsafe {
             if index < self.len {
@@ -105,39 +1062,913 @@ impl<T> Vec<T> {
             ptr::write(p.add(index), elem);
             self.len += 1;
         }
+        shadow!(self, "insert", |s, ours| s.insert(index, &ours[index]));
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.check_poison();
+        assert!(index < self.len, "index out of bounds");
+        unsafe {
+            self.len -= 1;
+            let p = self.buf.ptr.as_ptr();
+            let elem = ptr::read(p.add(index));
+            ptr::copy(p.add(self.len), p.add(index), 1);
+            self.asan_poison(self.len..self.len + 1);
+            self.maybe_shrink();
+            shadow!(self, "swap_remove", |s, _| s
+                .take(&elem, |v| v.swap_remove(index)));
+            elem
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.check_poison();
+        if len < self.len {
+            let old_len = self.len;
+            let tail = ptr::slice_from_raw_parts_mut(
+                unsafe { self.buf.ptr.as_ptr().add(len) },
+                old_len - len,
+            );
+            self.len = len;
+            if mem::needs_drop::<T>() {
+                self.poisoned = true;
+                unsafe { ptr::drop_in_place(tail) };
+                self.poisoned = false;
+            }
+            self.asan_poison(len..old_len);
+            self.maybe_shrink();
+            shadow!(self, "truncate", |s, _| s.edit(|v, _| v.truncate(len)));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [usize; N],
+    ) -> Result<[&mut T; N], GetDisjointMutError> {
+        let ranges = indices.map(|i| i..i.wrapping_add(1));
+        let slices = self.get_disjoint_ranges_mut(ranges)?;
+        Ok(slices.map(|s| &mut s[0]))
+    }
+
+    // Checks every pair, which is the cheapest option for the handful of ranges this is meant
+    // for. Empty ranges never overlap anything.
+    pub fn get_disjoint_ranges_mut<const N: usize>(
+        &mut self,
+        ranges: [Range<usize>; N],
+    ) -> Result<[&mut [T]; N], GetDisjointMutError> {
+        self.check_poison();
+        for (i, r) in ranges.iter().enumerate() {
+            if r.start > r.end || r.end > self.len {
+                return Err(GetDisjointMutError::IndexOutOfBounds);
+            }
+            for q in &ranges[..i] {
+                if r.start < q.end && q.start < r.end {
+                    return Err(GetDisjointMutError::OverlappingIndices);
+                }
+            }
+        }
+        shadow!(self, stale);
+        let p = self.buf.ptr.as_ptr();
+        Ok(ranges
+            .map(|r| unsafe { core::slice::from_raw_parts_mut(p.add(r.start), r.end - r.start) }))
     }
+
     pub fn remove(&mut self, index: usize) -> T {
+        self.check_poison();
         assert!(index < self.len, "index out of bounds");
         unsafe {
             self.len -= 1;
             let p = self.buf.ptr.as_ptr();
             let elem = ptr::read(p.add(index));
             ptr::copy(p.add(index + 1), p.add(index), self.len - index);
+            self.asan_poison(self.len..self.len + 1);
+            self.maybe_shrink();
+            shadow!(self, "remove", |s, _| s.take(&elem, |v| v.remove(index)));
             elem
         }
     }
+
+    // Drops the elements in `range` in place and closes the gap with one move of the tail. If
+    // a `Drop` panics, the vector is poisoned and cut at `range.start`, leaking the tail.
+    pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.check_poison();
+        let Range { start, end } = slice_range(range, self.len);
+        let old_len = self.len;
+        let p = self.buf.ptr.as_ptr();
+        if mem::needs_drop::<T>() {
+            self.len = start;
+            self.poisoned = true;
+            unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(p.add(start), end - start)) };
+            self.poisoned = false;
+        }
+        unsafe { ptr::copy(p.add(end), p.add(start), old_len - end) };
+        self.len = old_len - (end - start);
+        self.asan_poison(self.len..old_len);
+        self.maybe_shrink();
+        shadow!(self, "remove_range", |s, _| s
+            .edit(|v, _| drop(v.drain(start..end))));
+    }
+
+    // Removes and returns the first element matching `pred`, shifting the rest down.
+    pub fn find_remove<F: FnMut(&T) -> bool>(&mut self, pred: F) -> Option<T> {
+        self.check_poison();
+        let index = self.iter().position(pred)?;
+        Some(self.remove(index))
+    }
+
+    pub fn remove_first(&mut self, value: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        self.find_remove(|x| x == value)
+    }
+}
+
+#[cold]
+#[track_caller]
+fn poisoned_panic() -> ! {
+    panic!("vector was poisoned by a panic during a previous mutation")
+}
+
+// Heap usage of a container, for reporting per-subsystem memory metrics.
+pub trait MemoryReport {
+    fn allocated_bytes(&self) -> usize;
+    fn used_bytes(&self) -> usize;
+
+    fn spare_capacity_bytes(&self) -> usize {
+        self.allocated_bytes() - self.used_bytes()
+    }
+}
+
+impl<T, A: Allocator> MemoryReport for Vec<T, A> {
+    fn allocated_bytes(&self) -> usize {
+        if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            self.buf.cap * mem::size_of::<T>()
+        }
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.len * mem::size_of::<T>()
+    }
+}
+
+impl<T, A: Allocator + Default> Default for Vec<T, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T: Ord, A: Allocator> Vec<T, A> {
+    // Inserts after any equal elements, so repeated inserts keep their arrival order.
+    pub fn insert_sorted(&mut self, elem: T) -> usize {
+        debug_assert!(self.is_sorted(), "insert_sorted on unsorted vector");
+        let index = self.partition_point(|x| x <= &elem);
+        self.insert(index, elem);
+        index
+    }
+}
+
+impl<T> Vec<T> {
+    // Reinterprets the elements as `U`, keeping the allocation. Mismatched size or alignment is
+    // a compile-time error.
+    /// # Safety
+    ///
+    /// Every element must be a valid bit pattern for `U`.
+    pub unsafe fn cast<U>(self) -> Vec<U> {
+        const {
+            assert!(mem::size_of::<T>() == mem::size_of::<U>());
+            assert!(mem::align_of::<T>() == mem::align_of::<U>());
+        }
+        self.check_poison();
+        let (ptr, len, cap) = self.into_raw_parts();
+        unsafe { Vec::from_raw_parts(ptr as *mut U, len, cap) }
+    }
+
+    // Same as `cast`, but checks the layouts at runtime and hands `self` back on mismatch.
+    /// # Safety
+    ///
+    /// Every element must be a valid bit pattern for `U`.
+    pub unsafe fn try_cast<U>(self) -> Result<Vec<U>, Self> {
+        self.check_poison();
+        if mem::size_of::<T>() != mem::size_of::<U>()
+            || mem::align_of::<T>() != mem::align_of::<U>()
+        {
+            return Err(self);
+        }
+        let (ptr, len, cap) = self.into_raw_parts();
+        Ok(unsafe { Vec::from_raw_parts(ptr as *mut U, len, cap) })
+    }
+
+    // Clears the vector and reuses its allocation for `U`. The buffer is kept only when the
+    // alignments match and its size in bytes is a whole number of `U`s; otherwise it is freed
+    // and an unallocated vector is returned.
+    pub fn recycle<U>(mut self) -> Vec<U> {
+        self.check_poison();
+        // Not `clear`, whose shrink policy could free the very buffer being recycled. Should a
+        // destructor panic, `self` is dropped with nothing left to drop twice.
+        let elems = ptr::slice_from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len);
+        self.len = 0;
+        unsafe { ptr::drop_in_place(elems) };
+        let (size_t, size_u) = (mem::size_of::<T>(), mem::size_of::<U>());
+        if size_t == 0
+            || size_u == 0
+            || mem::align_of::<T>() != mem::align_of::<U>()
+            || !(self.capacity() * size_t).is_multiple_of(size_u)
+        {
+            return Vec::new();
+        }
+        let (ptr, _, cap) = self.into_raw_parts();
+        unsafe { Vec::from_raw_parts(ptr as *mut U, 0, cap * size_t / size_u) }
+    }
+}
+
+impl<T: Clone, A: Allocator> Vec<T, A> {
+    // Fallible `extend_from_slice`; nothing is appended if the reservation fails.
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), TryReserveError> {
+        self.check_poison();
+        self.try_reserve(other.len())?;
+        unsafe { self.clone_from_slice_within_capacity(other) };
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// The spare capacity must hold at least `other.len()` elements.
+    unsafe fn clone_from_slice_within_capacity(&mut self, other: &[T]) {
+        self.asan_unpoison(self.len..self.len + other.len());
+        #[cfg(feature = "shadow")]
+        let old_len = self.len;
+        let p = self.buf.ptr.as_ptr();
+        // A panicking `clone` leaves the elements written so far in the vector
+        let mut len = SetLenOnDrop::new(&mut self.len);
+        for elem in other {
+            unsafe { ptr::write(p.add(len.local_len), elem.clone()) };
+            len.local_len += 1;
+        }
+        drop(len);
+        shadow!(self, "extend_from_slice", |s, ours| s
+            .extend(&ours[old_len..]));
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T: Clone, A: Allocator> Vec<T, A> {
+    pub fn extend_from_slice(&mut self, other: &[T]) {
+        self.check_poison();
+        self.reserve(other.len());
+        unsafe { self.clone_from_slice_within_capacity(other) };
+    }
+
+    pub fn gather(&self, indices: &[usize]) -> Vec<T, A>
+    where
+        A: Clone,
+    {
+        self.check_poison();
+        for &i in indices {
+            assert!(i < self.len, "index out of bounds");
+        }
+        unsafe { self.gather_unchecked(indices) }
+    }
+
+    /// # Safety
+    ///
+    /// Every index in `indices` must be less than `self.len()`.
+    pub unsafe fn gather_unchecked(&self, indices: &[usize]) -> Vec<T, A>
+    where
+        A: Clone,
+    {
+        self.check_poison();
+        let mut out: Vec<T, A> = Vec::with_capacity_in(indices.len(), self.buf.alloc.clone());
+        out.asan_unpoison(0..indices.len());
+        let (src, dst) = (self.buf.ptr.as_ptr(), out.buf.ptr.as_ptr());
+        for (j, &i) in indices.iter().enumerate() {
+            strict_assert!(
+                i < self.len,
+                "gather_unchecked index {} out of bounds for length {}",
+                i,
+                self.len
+            );
+            unsafe { ptr::write(dst.add(j), (*src.add(i)).clone()) };
+            // Bumped per element so a panicking `clone` drops only what was written
+            out.len += 1;
+        }
+        out
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T: PartialEq, A: Allocator + Clone> Vec<T, A> {
+    // Collapses runs of equal elements, keeping the first of each run with its length.
+    pub fn dedup_and_count(self) -> Vec<(T, usize), A> {
+        let mut runs = Vec::new_in(self.buf.alloc.clone());
+        for elem in self {
+            match runs.last_mut() {
+                Some((last, count)) if *last == elem => *count += 1,
+                _ => runs.push((elem, 1)),
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "no_global_oom_handling")))]
+impl<T: core::hash::Hash + Eq, A: Allocator> Vec<T, A> {
+    // Removes every element equal to an earlier one, keeping first occurrences in their
+    // original order.
+    pub fn dedup_unsorted(&mut self) {
+        let mut seen = std::collections::HashSet::with_capacity(self.len);
+        let keep: Vec<bool> = self.iter().map(|x| seen.insert(x)).collect();
+        drop(seen);
+        self.retain_flags(&keep);
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "no_global_oom_handling")))]
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn dedup_unsorted_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: core::hash::Hash + Eq,
+        F: FnMut(&T) -> K,
+    {
+        let mut seen = std::collections::HashSet::with_capacity(self.len);
+        let keep: Vec<bool> = self.iter().map(|x| seen.insert(f(x))).collect();
+        self.retain_flags(&keep);
+    }
+
+    // Keeps `self[i]` iff `keep[i]`, preserving order. Decisions are made up front so that no
+    // element moves while user code looks at it; if a destructor panics, `Compact` closes the
+    // gap so the vector still holds only valid elements.
+    fn retain_flags(&mut self, keep: &[bool]) {
+        struct Compact<'a, T, A: Allocator> {
+            vec: &'a mut Vec<T, A>,
+            read: usize,
+            write: usize,
+            len: usize,
+        }
+
+        impl<T, A: Allocator> Drop for Compact<'_, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    let p = self.vec.buf.ptr.as_ptr();
+                    ptr::copy(p.add(self.read), p.add(self.write), self.len - self.read);
+                }
+                self.vec.len = self.write + self.len - self.read;
+                self.vec.asan_poison(self.vec.len..self.len);
+            }
+        }
+
+        self.check_poison();
+        shadow!(self, stale);
+        let len = self.len;
+        self.len = 0;
+        let mut g = Compact {
+            vec: self,
+            read: 0,
+            write: 0,
+            len,
+        };
+        let p = g.vec.buf.ptr.as_ptr();
+        while g.read < len {
+            let i = g.read;
+            g.read += 1;
+            unsafe {
+                if keep[i] {
+                    if i != g.write {
+                        ptr::copy_nonoverlapping(p.add(i), p.add(g.write), 1);
+                    }
+                    g.write += 1;
+                } else {
+                    ptr::drop_in_place(p.add(i));
+                }
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn insert_sorted_by_key<K, F>(&mut self, elem: T, mut f: F) -> usize
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        debug_assert!(
+            self.is_sorted_by_key(&mut f),
+            "insert_sorted_by_key on unsorted vector"
+        );
+        let key = f(&elem);
+        let index = self.partition_point(|x| f(x) <= key);
+        self.insert(index, elem);
+        index
+    }
+
+    // Returns the number of elements for which `pred` holds; they end up in front.
+    // The relative order within each side is not preserved.
+    pub fn partition_in_place<F>(&mut self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.check_poison();
+        let (mut left, mut right) = (0, self.len);
+        let p = self.buf.ptr.as_ptr();
+        // Stays poisoned if `pred` panics and leaves the elements half-partitioned
+        self.poisoned = true;
+        loop {
+            while left < right && pred(unsafe { &*p.add(left) }) {
+                left += 1;
+            }
+            while left < right && !pred(unsafe { &*p.add(right - 1) }) {
+                right -= 1;
+            }
+            if left == right {
+                self.poisoned = false;
+                return left;
+            }
+            unsafe { ptr::swap(p.add(left), p.add(right - 1)) };
+            left += 1;
+            right -= 1;
+        }
+    }
+}
+
+fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i.checked_add(1).expect("range start overflow"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&i) => i.checked_add(1).expect("range end overflow"),
+        Bound::Excluded(&i) => i,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range start is greater than range end");
+    assert!(end <= len, "range end out of bounds");
+    start..end
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    // Moves `range` out into a new, exactly sized vector and closes the gap.
+    pub fn take<R: RangeBounds<usize>>(&mut self, range: R) -> Vec<T, A>
+    where
+        A: Clone,
+    {
+        self.check_poison();
+        let Range { start, end } = slice_range(range, self.len);
+        let count = end - start;
+        let mut taken = Vec::with_capacity_in(count, self.buf.alloc.clone());
+        taken.asan_unpoison(0..count);
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            strict_assert!(
+                !overlaps(p.add(start), taken.buf.ptr.as_ptr(), count),
+                "take copies between overlapping buffers"
+            );
+            ptr::copy_nonoverlapping(p.add(start), taken.buf.ptr.as_ptr(), count);
+            taken.len = count;
+            ptr::copy(p.add(end), p.add(start), self.len - end);
+        }
+        self.len -= count;
+        self.asan_poison(self.len..self.len + count);
+        shadow!(self, "take", |s, _| s
+            .edit(|v, _| drop(v.drain(start..end))));
+        taken
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    // Splits into `n` contiguous vectors whose lengths differ by at most one.
+    pub fn split_into(self, n: usize) -> Vec<Vec<T, A>, A>
+    where
+        A: Clone,
+    {
+        self.check_poison();
+        assert!(n != 0, "cannot split into zero parts");
+        let (base, extra) = (self.len / n, self.len % n);
+        let mut sizes = Vec::with_capacity_in(n, self.buf.alloc.clone());
+        for i in 0..n {
+            sizes.push(base + (i < extra) as usize);
+        }
+        self.split_by_sizes(&sizes)
+    }
+
+    // Like `retain`, but fills each hole with the last element instead of shifting the tail.
+    pub fn retain_unordered<F>(&mut self, mut pred: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.check_poison();
+        let mut i = 0;
+        while i < self.len {
+            self.poisoned = true;
+            let keep = pred(unsafe { &*self.buf.ptr.as_ptr().add(i) });
+            self.poisoned = false;
+            if keep {
+                i += 1;
+            } else {
+                self.swap_remove(i);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    // Reorders so that `self[i]` becomes the old `self[perm[i]]`, using swaps along each
+    // cycle. Linear time, with one flag per element as scratch space.
+    pub fn permute_in_place(&mut self, perm: &[usize]) {
+        self.permute_in_place_with_scratch(perm, &mut Vec::new());
+    }
+
+    // Same as `permute_in_place` without allocating anything, at the price of a quadratic
+    // worst case for validating `perm` and finding cycle leaders: a single cycle through a
+    // million elements takes on the order of 10^12 steps. Only meant for short permutations.
+    pub fn permute_in_place_no_alloc(&mut self, perm: &[usize]) {
+        self.check_poison();
+        let n = self.len;
+        assert_eq!(perm.len(), n, "permutation length mismatch");
+        // Every index leads back to itself exactly when `perm` is a permutation. Checked before
+        // anything moves, so a bad `perm` leaves the vector untouched.
+        for i in 0..n {
+            let mut j = i;
+            for steps in 0.. {
+                j = perm[j];
+                assert!(j < n, "index out of bounds");
+                if j == i {
+                    break;
+                }
+                assert!(steps < n, "not a permutation");
+            }
+        }
+        for i in 0..n {
+            // Only rotate each cycle once, starting from its smallest index
+            let mut j = perm[i];
+            while j > i {
+                j = perm[j];
+            }
+            if j == i {
+                self.rotate_cycle(perm, i);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    // Same as `permute_in_place`, reusing `visited` as scratch space across calls.
+    pub fn permute_in_place_with_scratch(&mut self, perm: &[usize], visited: &mut Vec<bool>) {
+        self.check_poison();
+        let n = self.len;
+        assert_eq!(perm.len(), n, "permutation length mismatch");
+        visited.clear();
+        for _ in 0..n {
+            visited.push(false);
+        }
+        for &j in perm {
+            assert!(j < n && !visited[j], "not a permutation");
+            visited[j] = true;
+        }
+        for i in 0..n {
+            if visited[i] {
+                let mut j = i;
+                while visited[j] {
+                    visited[j] = false;
+                    j = perm[j];
+                }
+                self.rotate_cycle(perm, i);
+            }
+        }
+    }
+
+    fn rotate_cycle(&mut self, perm: &[usize], start: usize) {
+        let mut cur = start;
+        loop {
+            let next = perm[cur];
+            if next == start {
+                break;
+            }
+            self.swap(cur, next);
+            cur = next;
+        }
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn split_by_sizes(mut self, sizes: &[usize]) -> Vec<Vec<T, A>, A>
+    where
+        A: Clone,
+    {
+        self.check_poison();
+        // Checked, since a wrapped sum could match the length and copy past the buffer
+        let total = sizes
+            .iter()
+            .try_fold(0usize, |sum, &size| sum.checked_add(size));
+        assert_eq!(total, Some(self.len), "sizes must add up to the length");
+        // If an allocation below panics, the unmoved elements are leaked rather than double-dropped
+        self.len = 0;
+        let mut parts = Vec::with_capacity_in(sizes.len(), self.buf.alloc.clone());
+        let mut src = self.buf.ptr.as_ptr();
+        for &size in sizes {
+            let mut part = Vec::with_capacity_in(size, self.buf.alloc.clone());
+            part.asan_unpoison(0..size);
+            unsafe {
+                strict_assert!(
+                    !overlaps(src, part.buf.ptr.as_ptr(), size),
+                    "split_by_sizes copies between overlapping buffers"
+                );
+                ptr::copy_nonoverlapping(src, part.buf.ptr.as_ptr(), size);
+                part.len = size;
+                src = src.add(size);
+            }
+            parts.push(part);
+        }
+        parts
+    }
 }
 
-impl<T> Drop for Vec<T> {
+impl<T, A: Allocator> Drop for Vec<T, A> {
     fn drop(&mut self) {
-        if self.buf.cap != 0 {
-            // LLVM is smart enough to optimize the below if `T: !Drop`
-            while let Some(_) = self.pop() {}
-            // RawVec will dealloc the heap
+        // Drops front to back. If one element's `Drop` panics, `drop_in_place` still drops the
+        // rest, and `RawVec` frees the buffer as the fields are dropped during unwinding.
+        // Elements left behind by a poisoning panic are still valid, so they are dropped too.
+        if mem::needs_drop::<T>() {
+            let elems = ptr::slice_from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len);
+            self.len = 0;
+            unsafe { ptr::drop_in_place(elems) };
+        }
+    }
+}
+
+// Writes the length back when dropped, so that the elements written before a panic stay owned
+// by the vector while the hot loop keeps the length in a local.
+struct SetLenOnDrop<'a> {
+    len: &'a mut usize,
+    local_len: usize,
+}
+
+impl<'a> SetLenOnDrop<'a> {
+    fn new(len: &'a mut usize) -> Self {
+        let local_len = *len;
+        Self { len, local_len }
+    }
+}
+
+impl Drop for SetLenOnDrop<'_> {
+    fn drop(&mut self) {
+        *self.len = self.local_len;
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T, A: Allocator> Extend<T> for Vec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.check_poison();
+        #[cfg(feature = "shadow")]
+        let old_len = self.len;
+        let mut iter = iter.into_iter();
+        loop {
+            self.reserve(iter.size_hint().0);
+            self.asan_unpoison(self.len..self.buf.cap);
+            let p = self.buf.ptr.as_ptr();
+            let cap = self.buf.cap;
+            // Stays poisoned if the iterator panics. Set only now, so that a failed reserve
+            // leaves the vector usable.
+            self.poisoned = true;
+            let mut len = SetLenOnDrop::new(&mut self.len);
+            while len.local_len < cap {
+                match iter.next() {
+                    Some(elem) => {
+                        unsafe { ptr::write(p.add(len.local_len), elem) };
+                        len.local_len += 1;
+                    }
+                    None => {
+                        drop(len);
+                        self.poison_spare();
+                        self.poisoned = false;
+                        shadow!(self, "extend", |s, ours| s.extend(&ours[old_len..]));
+                        return;
+                    }
+                }
+            }
+            drop(len);
+            // The size hint was too low; fall back to growing one element at a time
+            let next = iter.next();
+            self.poisoned = false;
+            match next {
+                Some(elem) => {
+                    self.buf.grow();
+                    unsafe { ptr::write(self.buf.ptr.as_ptr().add(self.len), elem) };
+                    self.len += 1;
+                }
+                None => {
+                    self.poison_spare();
+                    shadow!(self, "extend", |s, ours| s.extend(&ours[old_len..]));
+                    return;
+                }
+            }
+        }
+    }
+
+    // A plain push, without the batch loop's poisoning and size-hint bookkeeping.
+    fn extend_one(&mut self, elem: T) {
+        self.push(elem);
+    }
+
+    // Lets `Iterator::unzip` and the tuple `Extend`/`FromIterator` impls in core reserve each
+    // output once up front.
+    fn extend_reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T: Copy, A: Allocator> Vec<T, A> {
+    // Like `extend_from_slice`, but `Copy` elements can't panic mid-way, so the whole slice goes
+    // over in one memcpy.
+    pub fn extend_from_copy_slice(&mut self, other: &[T]) {
+        self.check_poison();
+        self.reserve(other.len());
+        self.asan_unpoison(self.len..self.len + other.len());
+        #[cfg(feature = "shadow")]
+        let old_len = self.len;
+        unsafe {
+            let dst = self.buf.ptr.as_ptr().add(self.len);
+            ptr::copy_nonoverlapping(other.as_ptr(), dst, other.len());
         }
+        self.len += other.len();
+        shadow!(self, "extend_from_slice", |s, ours| s
+            .extend(&ours[old_len..]));
+    }
+}
+
+// Slice iterators hand over their remaining elements in one memcpy; any other iterator of
+// references goes through the generic loop.
+#[cfg(not(feature = "no_global_oom_handling"))]
+trait SpecExtendRef<'a, T: 'a, I> {
+    fn spec_extend_ref(&mut self, iter: I);
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<'a, T: Copy + 'a, I: Iterator<Item = &'a T>, A: Allocator> SpecExtendRef<'a, T, I>
+    for Vec<T, A>
+{
+    default fn spec_extend_ref(&mut self, iter: I) {
+        self.extend(iter.copied())
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<'a, T: Copy + 'a, A: Allocator> SpecExtendRef<'a, T, core::slice::Iter<'a, T>> for Vec<T, A> {
+    fn spec_extend_ref(&mut self, iter: core::slice::Iter<'a, T>) {
+        self.extend_from_copy_slice(iter.as_slice())
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for Vec<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        <Self as SpecExtendRef<'a, T, I::IntoIter>>::spec_extend_ref(self, iter.into_iter())
+    }
+
+    fn extend_one(&mut self, &elem: &'a T) {
+        self.push(elem);
+    }
+
+    fn extend_reserve(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T, A: Allocator + Default> core::iter::FromIterator<T> for Vec<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Vec::new_in(A::default());
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Vec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// Comparisons go through the slice impls, which core lowers to memcmp for bytes and to wide
+// compares for other integer primitives.
+impl<T: PartialEq<U>, U, A1: Allocator, A2: Allocator> PartialEq<Vec<U, A2>> for Vec<T, A1> {
+    fn eq(&self, other: &Vec<U, A2>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: PartialEq<U>, U, A: Allocator> PartialEq<[U]> for Vec<T, A> {
+    fn eq(&self, other: &[U]) -> bool {
+        **self == *other
+    }
+}
+
+impl<T: PartialEq<U>, U, A: Allocator> PartialEq<&[U]> for Vec<T, A> {
+    fn eq(&self, other: &&[U]) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: PartialEq<U>, U, A: Allocator, const N: usize> PartialEq<[U; N]> for Vec<T, A> {
+    fn eq(&self, other: &[U; N]) -> bool {
+        **self == other[..]
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Vec<T, A> {}
+
+impl<T: PartialOrd, A: Allocator> PartialOrd for Vec<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for Vec<T, A> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T: core::hash::Hash, A: Allocator> core::hash::Hash for Vec<T, A> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T> From<Vec<T>> for alloc::vec::Vec<T> {
+    fn from(v: Vec<T>) -> Self {
+        // Both sides allocate `Layout::array::<T>(cap)` from the global allocator
+        let (ptr, len, cap) = v.into_raw_parts();
+        unsafe { alloc::vec::Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl<T> From<alloc::vec::Vec<T>> for Vec<T> {
+    fn from(v: alloc::vec::Vec<T>) -> Self {
+        let mut v = mem::ManuallyDrop::new(v);
+        unsafe { Vec::from_raw_parts(v.as_mut_ptr(), v.len(), v.capacity()) }
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T> From<Vec<T>> for Box<[T]> {
+    fn from(mut v: Vec<T>) -> Self {
+        // The box is freed with a layout for exactly `len` elements, so a capacity lock can't
+        // be honoured here
+        v.buf.locked = false;
+        v.shrink_to_fit();
+        let (ptr, len, _) = v.into_raw_parts();
+        unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)) }
+    }
+}
+
+impl<T> From<Box<[T]>> for Vec<T> {
+    fn from(b: Box<[T]>) -> Self {
+        let len = b.len();
+        let ptr = Box::into_raw(b) as *mut T;
+        let cap = if mem::size_of::<T>() == 0 { !0 } else { len };
+        unsafe { Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+// Reference-counted slices need room for the counters in front of the data, so these copy
+// the elements exactly once.
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T> From<Vec<T>> for Arc<[T]> {
+    fn from(v: Vec<T>) -> Self {
+        Arc::from(alloc::vec::Vec::from(v))
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T> From<Vec<T>> for Rc<[T]> {
+    fn from(v: Vec<T>) -> Self {
+        Rc::from(alloc::vec::Vec::from(v))
     }
 }
 
-impl<T> Deref for Vec<T> {
+impl<T, A: Allocator> Deref for Vec<T, A> {
     type Target = [T];
+    #[inline]
     fn deref(&self) -> &Self::Target {
-        unsafe { std::slice::from_raw_parts(self.buf.ptr.as_ptr(), self.len) }
+        self.check_poison();
+        unsafe { core::slice::from_raw_parts(self.buf.ptr.as_ptr(), self.len) }
     }
 }
 
-impl<T> DerefMut for Vec<T> {
+impl<T, A: Allocator> DerefMut for Vec<T, A> {
+    #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { std::slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len) }
+        self.check_poison();
+        shadow!(self, stale);
+        unsafe { core::slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.len) }
     }
 }
 
@@ -156,6 +1987,49 @@ impl<T> RawIter<T> {
         };
         Self { start, end }
     }
+
+    fn as_slice(&self) -> &[T] {
+        let start = if mem::size_of::<T>() == 0 {
+            ptr::NonNull::dangling().as_ptr()
+        } else {
+            self.start
+        };
+        unsafe { core::slice::from_raw_parts(start, self.size_hint().0) }
+    }
+
+    // Moves the next `N` elements out as one array, if that many are left.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    fn next_array<const N: usize>(&mut self) -> Option<[T; N]> {
+        if self.size_hint().0 < N {
+            return None;
+        }
+        let src = self.as_slice().as_ptr() as *const [T; N];
+        self.start = if mem::size_of::<T>() == 0 {
+            (self.start as usize + N) as *const _
+        } else {
+            unsafe { self.start.add(N) }
+        };
+        Some(unsafe { ptr::read(src) })
+    }
+
+    // Drops up to `n` elements from the front in one `drop_in_place` and returns how many.
+    fn drop_front(&mut self, n: usize) -> usize {
+        let k = n.min(self.size_hint().0);
+        let skipped = ptr::slice_from_raw_parts_mut(self.as_slice().as_ptr() as *mut T, k);
+        // Advance first, so a panicking `Drop` can't lead to a double drop
+        self.start = if mem::size_of::<T>() == 0 {
+            (self.start as usize + k) as *const _
+        } else {
+            unsafe { self.start.add(k) }
+        };
+        unsafe { ptr::drop_in_place(skipped) };
+        k
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        let k = self.drop_front(n);
+        NonZeroUsize::new(n - k).map_or(Ok(()), Err)
+    }
 }
 
 impl<T> DoubleEndedIterator for RawIter<T> {
@@ -165,9 +2039,9 @@ impl<T> DoubleEndedIterator for RawIter<T> {
         } else {
             unsafe {
                 self.end = if mem::size_of::<T>() == 0 {
-                    self.end.sub(1)
-                } else {
                     (self.end as usize - 1) as *const _
+                } else {
+                    self.end.sub(1)
                 };
                 let ret = ptr::read(self.end);
                 Some(ret)
@@ -204,15 +2078,25 @@ impl<T> Iterator for RawIter<T> {
     }
 }
 
-struct IntoIter<T> {
-    _buf: RawVec<T>, // just holds the ownership
+pub struct IntoIter<T, A: Allocator = Global> {
+    _buf: RawVec<T, A>, // just holds the ownership
     iter: RawIter<T>,
 }
 
-impl<T> IntoIterator for Vec<T> {
-    type IntoIter = IntoIter<T>;
+// The raw pointers in `RawIter` only ever point at elements the iterator owns.
+unsafe impl<T: Send, A: Allocator + Send> Send for IntoIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for IntoIter<T, A> {}
+
+impl<T, A: Allocator> IntoIterator for Vec<T, A> {
+    type IntoIter = IntoIter<T, A>;
     type Item = T;
-    fn into_iter(self) -> Self::IntoIter {
+    #[cfg_attr(not(feature = "shadow"), allow(unused_mut))]
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.check_poison();
+        #[cfg(feature = "shadow")]
+        {
+            self.shadow = None;
+        }
         // Destruction calls `Vec::drop`, so unsafe read is required
         unsafe {
             let iter = RawIter::new(&self);
@@ -225,7 +2109,24 @@ impl<T> IntoIterator for Vec<T> {
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> IntoIter<T, A> {
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+}
+
+// Only the remaining elements are cloned, into a buffer of exactly their length.
+#[cfg(not(feature = "no_global_oom_handling"))]
+impl<T: Clone, A: Allocator + Clone> Clone for IntoIter<T, A> {
+    fn clone(&self) -> Self {
+        let rest = self.as_slice();
+        let mut v = Vec::with_capacity_in(rest.len(), self._buf.alloc.clone());
+        v.extend_from_slice(rest);
+        v.into_iter()
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.iter.next()
@@ -233,26 +2134,44 @@ impl<T> Iterator for IntoIter<T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
     }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.drop_front(n);
+        self.iter.next()
+    }
+    fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        self.iter.advance_by(n)
+    }
+    fn count(self) -> usize {
+        self.iter.size_hint().0
+    }
+    fn last(mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter.next_back()
     }
 }
 
-impl<T> Drop for IntoIter<T> {
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
     fn drop(&mut self) {
-        for _ in &mut self.iter {}
+        if mem::needs_drop::<T>() {
+            self.iter.drop_front(usize::MAX);
+        }
     }
 }
 
-struct Drain<'a, T: 'a> {
-    vec: PhantomData<&'a mut Vec<T>>,
+pub struct Drain<'a, T: 'a, A: Allocator = Global> {
+    vec: PhantomData<&'a mut Vec<T, A>>,
     iter: RawIter<T>,
 }
 
-impl<'a, T> Iterator for Drain<'a, T> {
+unsafe impl<T: Send, A: Allocator + Send> Send for Drain<'_, T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Drain<'_, T, A> {}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.iter.next()
@@ -260,25 +2179,42 @@ impl<'a, T> Iterator for Drain<'a, T> {
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
     }
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.drop_front(n);
+        self.iter.next()
+    }
+    fn advance_by(&mut self, n: usize) -> Result<(), NonZeroUsize> {
+        self.iter.advance_by(n)
+    }
+    fn count(self) -> usize {
+        self.iter.size_hint().0
+    }
+    fn last(mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
 }
 
-impl<'a, T> DoubleEndedIterator for Drain<'a, T> {
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.iter.next_back()
     }
 }
 
-impl<'a, T> Drop for Drain<'a, T> {
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
     fn drop(&mut self) {
-        for _ in &mut self.iter {}
+        if mem::needs_drop::<T>() {
+            self.iter.drop_front(usize::MAX);
+        }
     }
 }
 
-impl<T> Vec<T> {
-    pub fn drain<'a>(&'a mut self) -> Drain<'a, T> {
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn drain<'a>(&'a mut self) -> Drain<'a, T, A> {
+        self.check_poison();
         unsafe {
-            let iter = RawIter::new(&self);
+            let iter = RawIter::new(self);
             self.len = 0;
+            shadow!(self, "drain", |s, _| s.edit(|v, _| v.clear()));
             Drain {
                 vec: PhantomData,
                 iter,
@@ -287,9 +2223,12 @@ impl<T> Vec<T> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
 mod tests {
     use super::*;
+    use core::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::println;
     fn new_vec(n: usize) -> Vec<Box<usize>> {
         let mut a = Vec::new();
         for i in 0..n {
@@ -297,13 +2236,16 @@ mod tests {
         }
         a
     }
+    fn unbox(a: &[Box<usize>]) -> std::vec::Vec<usize> {
+        a.iter().map(|x| **x).collect()
+    }
     #[test]
     fn grow() {
         let mut a = RawVec::<usize>::new();
         a.grow();
-        assert!(a.cap == 1);
+        assert!(a.cap == 4);
         a.grow();
-        assert!(a.cap == 2);
+        assert!(a.cap == 8);
         println!("OK!");
     }
     #[test]
@@ -409,5 +2351,931 @@ mod tests {
         for i in a {
             assert_eq!(i, A)
         }
+
+        let mut a = Vec::new();
+        for _ in 0..n {
+            a.push(A);
+        }
+        assert_eq!(a.into_iter().rev().count(), n);
+    }
+
+    #[test]
+    fn extend_from_slices() {
+        let mut a = Vec::new();
+        a.extend([1u32, 2, 3].iter());
+        a.extend(&[4, 5]);
+        a.extend_from_slice(&[6]);
+        assert_eq!(a, [1, 2, 3, 4, 5, 6]);
+        let mut rest = [7u32, 8, 9].iter();
+        rest.next();
+        a.extend(rest);
+        a.extend([10u32, 11, 12].iter().step_by(2));
+        a.extend_from_copy_slice(&[13, 14]);
+        assert_eq!(a, [1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 13, 14]);
+
+        let mut b = new_vec(2);
+        b.extend_from_slice(&new_vec(3));
+        assert_eq!(unbox(&b), [0, 1, 0, 1, 2]);
+
+        let drops = Cell::new(0);
+        let mut src = [
+            Tracked::new(&drops),
+            Tracked::new(&drops),
+            Tracked::new(&drops),
+        ];
+        src[1].panic_on_clone = true;
+        let mut c = Vec::new();
+        let r = catch_unwind(AssertUnwindSafe(|| c.extend_from_slice(&src)));
+        assert!(r.is_err());
+        assert_eq!(c.len(), 1);
+        drop(c);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn clone_into_iter() {
+        let mut it = new_vec(5).into_iter();
+        it.next();
+        it.next_back();
+        let fork = it.clone();
+        assert_eq!(unbox(fork.as_slice()), [1, 2, 3]);
+        assert_eq!(fork.map(|x| *x).collect::<std::vec::Vec<_>>(), [1, 2, 3]);
+        it.next();
+        assert_eq!(unbox(it.as_slice()), [2, 3]);
+        assert_eq!(it.clone()._buf.cap, 2);
+
+        let zst = core::iter::repeat_n((), 3).collect::<Vec<()>>().into_iter();
+        assert_eq!(zst.clone().count(), 3);
+    }
+
+    #[test]
+    fn skipping_iterators() {
+        let mut it = new_vec(10).into_iter();
+        assert_eq!(it.nth(3).map(|x| *x), Some(3));
+        assert_eq!(it.advance_by(2), Ok(()));
+        assert_eq!(it.as_slice().len(), 4);
+        assert_eq!(it.clone().count(), 4);
+        assert_eq!(it.clone().last().map(|x| *x), Some(9));
+        assert_eq!(it.advance_by(10), Err(NonZeroUsize::new(6).unwrap()));
+        assert_eq!(it.nth(1), None);
+
+        let mut a = new_vec(6);
+        let mut d = a.drain();
+        assert_eq!(d.nth(4).map(|x| *x), Some(4));
+        assert_eq!(d.last().map(|x| *x), Some(5));
+        assert!(a.is_empty());
+
+        let mut z = core::iter::repeat_n((), 5).collect::<Vec<()>>().into_iter();
+        assert_eq!(z.nth(3), Some(()));
+        assert_eq!(z.count(), 1);
+
+        // Skipped elements are dropped exactly once, even if one of their drops panics
+        let drops = Cell::new(0);
+        let mut v = Vec::new();
+        for _ in 0..4 {
+            v.push(Tracked::new(&drops));
+        }
+        v[1].panic_on_drop = true;
+        let mut it = v.into_iter();
+        assert!(catch_unwind(AssertUnwindSafe(|| it.nth(2))).is_err());
+        drop(it);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn try_with_capacity() {
+        let v = Vec::<u64>::try_with_capacity(10).unwrap();
+        assert_eq!((v.len(), v.capacity()), (0, 10));
+        assert_eq!(
+            Vec::<u64>::try_with_capacity(usize::MAX / 4).unwrap_err(),
+            TryReserveError::CapacityOverflow
+        );
+        assert!(matches!(
+            Vec::<u8>::try_with_capacity(isize::MAX as usize),
+            Err(TryReserveError::AllocError { .. })
+        ));
+        assert_eq!(
+            Vec::<()>::try_with_capacity(usize::MAX).unwrap().capacity(),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn disjoint_mut() {
+        let mut a = new_vec(6);
+        let [x, y] = a.get_disjoint_mut([4, 1]).unwrap();
+        mem::swap(x, y);
+        **y += 10;
+        assert_eq!(unbox(&a), [0, 14, 2, 3, 1, 5]);
+
+        let [l, r, e] = a.get_disjoint_ranges_mut([0..2, 3..6, 2..2]).unwrap();
+        l.swap_with_slice(&mut r[1..]);
+        assert!(e.is_empty());
+        assert_eq!(unbox(&a), [1, 5, 2, 3, 0, 14]);
+
+        assert_eq!(
+            a.get_disjoint_mut([2, 2]).err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+        assert_eq!(
+            a.get_disjoint_mut([0, 6]).err(),
+            Some(GetDisjointMutError::IndexOutOfBounds)
+        );
+        assert_eq!(
+            a.get_disjoint_ranges_mut([1..4, 3..5]).err(),
+            Some(GetDisjointMutError::OverlappingIndices)
+        );
+        assert_eq!(
+            a.get_disjoint_mut([usize::MAX]).err(),
+            Some(GetDisjointMutError::IndexOutOfBounds)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn dedup_unsorted() {
+        let mut a: Vec<usize> = [3, 1, 3, 2, 1, 4, 2].iter().copied().collect();
+        a.dedup_unsorted();
+        assert_eq!(&a[..], &[3, 1, 2, 4]);
+
+        let mut a = new_vec(10);
+        a.dedup_unsorted_by_key(|x| **x % 4);
+        assert_eq!(unbox(&a), [0, 1, 2, 3]);
+
+        let drops = Cell::new(0);
+        let mut a: Vec<Tracked> = (0..6).map(|_| Tracked::new(&drops)).collect();
+        a[3].panic_on_drop = true;
+        let r = catch_unwind(AssertUnwindSafe(|| a.dedup_unsorted_by_key(|_| 0)));
+        assert!(r.is_err());
+        assert_eq!(drops.get(), 3);
+        assert_eq!(a.len(), 3);
+    }
+
+    #[test]
+    fn find_remove() {
+        let mut a = new_vec(6);
+        assert_eq!(a.find_remove(|x| **x > 2).map(|x| *x), Some(3));
+        assert_eq!(a.remove_first(&Box::new(0)).map(|x| *x), Some(0));
+        assert_eq!(a.remove_first(&Box::new(0)), None);
+        assert_eq!(a.find_remove(|_| false), None);
+        assert_eq!(unbox(&a), [1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn lock_capacity() {
+        let mut a = Vec::with_capacity(4).with_shrink_policy(ShrinkPolicy::below_fraction(4));
+        a.lock_capacity();
+        for i in 0..4 {
+            a.try_push(Box::new(i)).unwrap();
+        }
+        assert_eq!(
+            a.try_push(Box::new(4)),
+            Err((TryReserveError::CapacityLocked, Box::new(4)))
+        );
+        assert_eq!(a.try_reserve(1), Err(TryReserveError::CapacityLocked));
+        a.reserve(0);
+        let r = catch_unwind(AssertUnwindSafe(|| a.push(Box::new(4))));
+        assert!(r.is_err());
+        a.truncate(0);
+        a.shrink_to_fit();
+        assert_eq!(a.capacity(), 4);
+
+        a.unlock_capacity();
+        assert!(!a.is_capacity_locked());
+        a.extend((0..5).map(Box::new));
+        assert_eq!(unbox(&a), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn max_capacity() {
+        let mut a = Vec::<u32>::new().with_max_capacity(6);
+        assert_eq!(a.max_capacity(), Some(6));
+        a.extend(0..4);
+        assert_eq!(a.capacity(), 4);
+        // Doubling would give 8
+        a.push(4);
+        assert_eq!(a.capacity(), 6);
+        a.push(5);
+        assert_eq!(a.try_push(6), Err((TryReserveError::CapacityOverflow, 6)));
+        assert_eq!(a.try_reserve(1), Err(TryReserveError::CapacityOverflow));
+        let r = catch_unwind(AssertUnwindSafe(|| a.push(6)));
+        assert!(r.is_err());
+        assert_eq!(a, [0, 1, 2, 3, 4, 5]);
+
+        a.set_max_capacity(None);
+        assert_eq!(a.max_capacity(), None);
+        a.push(6);
+        assert_eq!(a.len(), 7);
+    }
+
+    // Compile-time guarantees; the negative cases live in tests/ui.
+    #[test]
+    fn auto_traits() {
+        fn send_sync<T: Send + Sync>() {}
+        send_sync::<Vec<u8>>();
+        send_sync::<IntoIter<u8>>();
+        send_sync::<Drain<'_, u8>>();
+    }
+
+    #[test]
+    fn covariance() {
+        // The shadow copy's captured `Clone`/`PartialEq` make `Vec` invariant (see src/shadow.rs
+        // and tests/ui/shadow), but `IntoIter` doesn't carry one
+        #[cfg(not(feature = "shadow"))]
+        fn covariant<'a>(v: Vec<&'static str>) -> Vec<&'a str> {
+            v
+        }
+        fn covariant_iter<'a>(it: IntoIter<&'static str>) -> IntoIter<&'a str> {
+            it
+        }
+        let mut v = Vec::new();
+        v.push("a");
+        #[cfg(not(feature = "shadow"))]
+        let v = covariant(v);
+        assert_eq!(covariant_iter(v.into_iter()).next(), Some("a"));
+    }
+
+    #[test]
+    fn push_within_capacity() {
+        let mut a = Vec::with_capacity(4);
+        unsafe {
+            a.push_unchecked(1);
+            a.push_unchecked(2);
+        }
+        let mut rest = 3..10;
+        assert_eq!(a.extend_within_capacity(rest.by_ref()), 2);
+        assert_eq!(a, [1, 2, 3, 4]);
+        assert_eq!(rest, 5..10);
+        assert_eq!(a.extend_within_capacity(rest), 0);
+        a.reserve(3);
+        assert_eq!(a.extend_within_capacity([5, 6]), 2);
+        assert_eq!(a.len(), 6);
+    }
+
+    #[test]
+    fn drop_fast_paths() {
+        let drops = Cell::new(0);
+        let tracked = |n| (0..n).map(|_| Tracked::new(&drops)).collect::<Vec<_>>();
+        let mut a = tracked(5);
+        a.truncate(2);
+        assert_eq!(drops.get(), 3);
+        drop(a);
+        let mut it = tracked(4).into_iter();
+        it.next();
+        drop(it);
+        tracked(3).drain().next();
+        assert_eq!(drops.get(), 12);
+
+        // Trivially droppable elements take the same paths with nothing to run
+        let mut b: Vec<u64> = (0..100).collect();
+        b.truncate(10);
+        assert_eq!(b.len(), 10);
+        b.clear();
+        assert!(b.is_empty());
+        drop((0..100u64).collect::<Vec<_>>().into_iter().skip(3));
+        drop((0..100u64).collect::<Vec<_>>().drain());
+    }
+
+    #[test]
+    fn unzip() {
+        let (a, b): (Vec<u32>, Vec<char>) = (0..10).map(|i| (i, (b'a' + i as u8) as char)).unzip();
+        assert_eq!(a, (0..10).collect::<Vec<_>>());
+        assert_eq!(b.iter().collect::<std::string::String>(), "abcdefghij");
+        assert_eq!((a.capacity(), b.capacity()), (10, 10));
+        let (c, d): (Vec<_>, Vec<_>) = [(1, 2), (3, 4)].iter().copied().collect();
+        assert_eq!(c, [1, 3]);
+        assert_eq!(d, [2, 4]);
+    }
+
+    #[test]
+    fn extend_one() {
+        fn fill<E: Extend<u8>>(e: &mut E) {
+            e.extend_reserve(3);
+            for b in 1..=3 {
+                e.extend_one(b);
+            }
+        }
+        let mut a = Vec::new();
+        fill(&mut a);
+        assert_eq!(a, [1, 2, 3]);
+        assert_eq!(a.capacity(), 8);
+        Extend::<&u8>::extend_reserve(&mut a, 10);
+        a.extend_one(&4);
+        assert_eq!(a, [1, 2, 3, 4]);
+        assert!(a.capacity() >= 13);
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut a: Vec<usize> = (0..10).collect();
+        a.remove_range(2..5);
+        assert_eq!(a, [0, 1, 5, 6, 7, 8, 9]);
+        a.remove_range(..=1);
+        a.remove_range(3..);
+        a.remove_range(1..1);
+        assert_eq!(a, [5, 6, 7]);
+
+        let drops = Cell::new(0);
+        let mut b = Vec::from_fn(6, |_| Tracked::new(&drops));
+        b.remove_range(1..4);
+        assert_eq!((b.len(), drops.get()), (3, 3));
+        assert!(catch_unwind(AssertUnwindSafe(|| b.remove_range(..4))).is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        use core::cmp::Ordering;
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        a.extend(0u8..=255);
+        b.extend(0u8..=255);
+        assert_eq!(a, b);
+        assert_eq!(a[..3], [0, 1, 2]);
+        b.push(0);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        a[200] = 255;
+        assert!(a > b);
+        assert_ne!(a, b);
+
+        let mut c = Vec::new();
+        c.extend([1.0f64, f64::NAN]);
+        assert_ne!(c, c);
+        assert_eq!(c.partial_cmp(&c), None);
+        assert!(new_vec(3) == [Box::new(0), Box::new(1), Box::new(2)]);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn traces_reallocations() {
+        use std::sync::atomic::AtomicUsize;
+        use tracing::subscriber::with_default;
+        use tracing::{span, Event, Level, Metadata, Subscriber};
+
+        #[derive(Default)]
+        struct Counter {
+            debug: AtomicUsize,
+            warn: AtomicUsize,
+        }
+        impl Subscriber for &'static Counter {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                match *event.metadata().level() {
+                    Level::WARN => &self.warn,
+                    _ => &self.debug,
+                }
+                .fetch_add(1, Ordering::Relaxed);
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let counter: &'static Counter = Box::leak(Box::default());
+        with_default(counter, || {
+            let mut a = Vec::<u64>::new();
+            for i in 0..5 {
+                a.push(i);
+            }
+            a.shrink_to_fit();
+            a.reserve(super::LARGE_ALLOCATION_BYTES / 8);
+        });
+        // Growth to 4 and 8, a shrink to 5, then a large reserve.
+        assert_eq!(counter.debug.load(Ordering::Relaxed), 3);
+        assert_eq!(counter.warn.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn recycle() {
+        let mut a = Vec::<u64>::with_capacity(10);
+        a.push(1);
+        let ptr = a.as_ptr() as usize;
+        let b: Vec<f64> = a.recycle();
+        assert_eq!((b.len(), b.capacity(), b.as_ptr() as usize), (0, 10, ptr));
+        let c: Vec<[i64; 2]> = b.recycle();
+        assert_eq!((c.capacity(), c.as_ptr() as usize), (5, ptr));
+        let d: Vec<u8> = c.recycle();
+        assert_eq!(d.capacity(), 0);
+
+        let mut e = new_vec(5);
+        e.push(Box::new(5));
+        let f: Vec<usize> = e.recycle();
+        assert!(f.capacity() >= 6);
+
+        // The shrink policy doesn't get to free the buffer first
+        let drops = Cell::new(0);
+        let mut g = Vec::with_capacity(64).with_shrink_policy(ShrinkPolicy::below_fraction(4));
+        g.push(Tracked::new(&drops));
+        g.push(Tracked::new(&drops));
+        let ptr = g.as_ptr() as usize;
+        let h: Vec<[usize; 2]> = g.recycle();
+        assert_eq!((h.capacity(), h.as_ptr() as usize), (64, ptr));
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn insert_sorted() {
+        let mut a = Vec::new();
+        for i in [5, 1, 4, 1, 3, 9, 2, 6] {
+            a.insert_sorted(i);
+        }
+        assert_eq!(&a[..], &[1, 1, 2, 3, 4, 5, 6, 9]);
+        assert_eq!(a.insert_sorted(4), 5);
+
+        let mut b = Vec::new();
+        for (i, k) in [(0, 3), (1, 1), (2, 3), (3, 2)] {
+            b.insert_sorted_by_key((i, k), |e| e.1);
+        }
+        assert_eq!(&b[..], &[(1, 1), (3, 2), (0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn partition_in_place() {
+        let mut a = Vec::new();
+        for i in 0..100 {
+            a.push(i);
+        }
+        let k = a.partition_in_place(|x| x % 3 == 0);
+        assert_eq!(k, 34);
+        assert!(a[..k].iter().all(|x| x % 3 == 0));
+        assert!(a[k..].iter().all(|x| x % 3 != 0));
+        assert_eq!(a.partition_in_place(|_| true), 100);
+        assert_eq!(a.partition_in_place(|_| false), 0);
+    }
+
+    #[test]
+    fn dedup_and_count() {
+        let mut a = Vec::new();
+        for i in [1, 1, 2, 3, 3, 3, 1] {
+            a.push(Box::new(i));
+        }
+        let runs = a.dedup_and_count();
+        let runs: std::vec::Vec<_> = runs.iter().map(|(e, n)| (**e, *n)).collect();
+        assert_eq!(runs, [(1, 2), (2, 1), (3, 3), (1, 1)]);
+        assert_eq!(Vec::<usize>::new().dedup_and_count().len(), 0);
+    }
+
+    #[test]
+    fn take() {
+        let mut a = new_vec(10);
+        let b = a.take(2..5);
+        assert_eq!(b.capacity(), 3);
+        assert_eq!(unbox(&b), [2, 3, 4]);
+        assert_eq!(unbox(&a), [0, 1, 5, 6, 7, 8, 9]);
+        assert_eq!(a.take(..).len(), 7);
+        assert_eq!(a.len(), 0);
+    }
+
+    #[test]
+    fn split_into() {
+        let parts = new_vec(10).split_into(3);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(unbox(&parts[0]), [0, 1, 2, 3]);
+        assert_eq!(unbox(&parts[1]), [4, 5, 6]);
+        assert_eq!(unbox(&parts[2]), [7, 8, 9]);
+        assert_eq!(parts[2].capacity(), 3);
+
+        let parts = new_vec(5).split_by_sizes(&[0, 5, 0]);
+        assert_eq!(unbox(&parts[1]), [0, 1, 2, 3, 4]);
+        assert_eq!(parts[0].len() + parts[2].len(), 0);
+
+        // Wraps around to 1 without checked addition
+        let r = catch_unwind(AssertUnwindSafe(|| {
+            new_vec(1).split_by_sizes(&[2, usize::MAX])
+        }));
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn gather() {
+        let a = new_vec(10);
+        let b = a.gather(&[9, 0, 3, 3]);
+        assert_eq!(unbox(&b), [9, 0, 3, 3]);
+        assert_eq!(b.capacity(), 4);
+        assert_eq!(a.gather(&[]).len(), 0);
+    }
+
+    #[test]
+    fn permute_in_place() {
+        let perm = [3, 0, 4, 1, 2, 5];
+        let mut a = new_vec(6);
+        a.permute_in_place(&perm);
+        assert_eq!(unbox(&a), perm);
+
+        let mut a = new_vec(6);
+        let mut visited = Vec::new();
+        a.permute_in_place_with_scratch(&perm, &mut visited);
+        assert_eq!(unbox(&a), perm);
+
+        let mut a = new_vec(6);
+        a.permute_in_place_no_alloc(&perm);
+        assert_eq!(unbox(&a), perm);
+
+        for bad in [&[0, 0][..], &[1, 2, 1], &[2, 0, 3]] {
+            let mut a = new_vec(bad.len());
+            let res = catch_unwind(AssertUnwindSafe(|| a.permute_in_place(bad)));
+            assert!(res.is_err());
+            let res = catch_unwind(AssertUnwindSafe(|| a.permute_in_place_no_alloc(bad)));
+            assert!(res.is_err());
+            assert_eq!(unbox(&a), (0..bad.len()).collect::<std::vec::Vec<_>>());
+        }
+
+        // One long cycle, which the allocation-free variant would need quadratic time for
+        let n = 1 << 20;
+        let mut a: Vec<usize> = (0..n).collect();
+        let perm: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+        a.permute_in_place(&perm);
+        assert_eq!(a, perm);
+    }
+
+    #[test]
+    fn retain_unordered() {
+        let mut a = new_vec(10);
+        a.retain_unordered(|x| **x % 2 == 0);
+        let mut kept = unbox(&a);
+        kept.sort();
+        assert_eq!(kept, [0, 2, 4, 6, 8]);
+        assert_eq!(*a.swap_remove(0), 0);
+        assert_eq!(a.len(), 4);
+    }
+
+    #[test]
+    fn cast() {
+        #[derive(Debug, PartialEq)]
+        #[repr(transparent)]
+        struct Wrapper(u32);
+        let mut a = Vec::new();
+        for i in 0..10 {
+            a.push(Wrapper(i));
+        }
+        let cap = a.capacity();
+        let b: Vec<u32> = unsafe { a.cast() };
+        assert_eq!(b.capacity(), cap);
+        assert_eq!(&b[..3], &[0, 1, 2]);
+        let c = unsafe { b.try_cast::<u64>() }.unwrap_err();
+        let d = unsafe { c.try_cast::<Wrapper>() }.unwrap();
+        assert_eq!(d[9], Wrapper(9));
+    }
+
+    #[test]
+    fn std_conversion() {
+        let mut a = new_vec(10);
+        a.reserve(20);
+        assert!(a.capacity() >= 30);
+        let cap = a.capacity();
+        let b: std::vec::Vec<_> = a.into();
+        assert_eq!(b.capacity(), cap);
+        let c: Vec<_> = b.into();
+        assert_eq!(unbox(&c), (0..10).collect::<std::vec::Vec<_>>());
+    }
+
+    #[test]
+    fn boxed_and_shared_slices() {
+        let mut a = new_vec(5);
+        a.reserve(100);
+        let b: Box<[Box<usize>]> = a.into();
+        assert_eq!(unbox(&b), [0, 1, 2, 3, 4]);
+        let ptr = b.as_ptr();
+        let a: Vec<_> = b.into();
+        assert_eq!(a.as_ptr(), ptr);
+        assert_eq!(a.capacity(), 5);
+
+        let arc: Arc<[Box<usize>]> = a.into();
+        assert_eq!(unbox(&arc), [0, 1, 2, 3, 4]);
+        let rc: Rc<[Box<usize>]> = new_vec(3).into();
+        assert_eq!(unbox(&rc), [0, 1, 2]);
+
+        let mut z = Vec::new();
+        z.push(());
+        let z: Box<[()]> = z.into();
+        assert_eq!(Vec::from(z).len(), 1);
+
+        let mut locked = Vec::with_capacity(16);
+        locked.push(7u32);
+        locked.lock_capacity();
+        let b: Box<[u32]> = locked.into();
+        assert_eq!(Vec::from(b).capacity(), 1);
+    }
+
+    #[test]
+    fn shrink_policy() {
+        let mut a = new_vec(64).with_shrink_policy(ShrinkPolicy::below_fraction(4));
+        assert_eq!(a.capacity(), 64);
+        while a.len() > 16 {
+            a.pop();
+        }
+        assert_eq!(a.capacity(), 64);
+        a.pop();
+        assert_eq!(a.capacity(), 32);
+        a.truncate(3);
+        assert_eq!(a.capacity(), 8);
+        assert_eq!(unbox(&a), [0, 1, 2]);
+        a.remove(0);
+        a.clear();
+        assert_eq!(a.capacity(), 2);
+
+        let mut b = new_vec(64);
+        b.truncate(1);
+        assert_eq!(b.capacity(), 64);
+    }
+
+    #[test]
+    fn memory_report() {
+        let mut a = Vec::<u32>::with_capacity(10);
+        a.push(1);
+        assert_eq!(a.allocated_bytes(), 40);
+        assert_eq!(a.used_bytes(), 4);
+        assert_eq!(a.spare_capacity_bytes(), 36);
+
+        let mut z = Vec::new();
+        z.push(());
+        assert_eq!(z.allocated_bytes(), 0);
+        assert_eq!(Vec::<u64>::new().allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut a = Vec::<u64>::new();
+        assert_eq!(a.try_reserve(10), Ok(()));
+        assert!(a.capacity() >= 10);
+        assert_eq!(
+            a.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert_eq!(
+            a.try_reserve(isize::MAX as usize / 8 + 1),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        a.try_push(2).unwrap();
+        a.try_insert(0, 1).unwrap();
+        assert_eq!(&a[..], &[1, 2]);
+    }
+
+    #[test]
+    fn alloc_error_hook() {
+        use core::sync::atomic::AtomicUsize;
+        const SIZE: usize = isize::MAX as usize - 1;
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        fn hook(layout: Layout) -> bool {
+            // The hook is process-wide; failures from tests running in parallel don't count
+            if layout.size() != SIZE {
+                return false;
+            }
+            // Retry once, then give up
+            CALLS.fetch_add(1, Ordering::SeqCst) == 0
+        }
+        set_alloc_error_hook(Some(hook));
+        let mut a = Vec::<u8>::new();
+        let res = a.try_reserve(SIZE);
+        set_alloc_error_hook(None);
+        assert!(matches!(res, Err(TryReserveError::AllocError { .. })));
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "strict")]
+    #[should_panic(expected = "exceeds capacity")]
+    fn strict_set_len() {
+        let mut a = Vec::<u8>::with_capacity(4);
+        unsafe { a.set_len(5) };
+    }
+
+    #[test]
+    fn poison() {
+        let mut a = new_vec(10);
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            a.retain_unordered(|x| {
+                assert!(**x != 5);
+                **x % 2 == 0
+            })
+        }));
+        assert!(res.is_err());
+        assert!(a.is_poisoned());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.len())).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.push(Box::new(0)))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.reserve(1))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.take(0..1))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.retain_unordered(|_| true))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.permute_in_place(&[]))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| a.gather(&[]))).is_err());
+        a.clear_poison();
+        assert_eq!(a.len(), 6);
+
+        let mut b: Vec<u32> = (0..4).collect();
+        b.poisoned = true;
+        assert!(catch_unwind(AssertUnwindSafe(|| mem::take(&mut b).split_into(2))).is_err());
+        b.poisoned = true;
+        assert!(catch_unwind(AssertUnwindSafe(|| mem::take(&mut b).recycle::<i32>())).is_err());
+        b.poisoned = true;
+        assert!(catch_unwind(AssertUnwindSafe(|| unsafe {
+            mem::take(&mut b).cast::<i32>()
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn failed_reserve_does_not_poison() {
+        let mut a: Vec<u32> = Vec::with_capacity(2);
+        a.lock_capacity();
+        assert!(catch_unwind(AssertUnwindSafe(|| a.extend(0..3))).is_err());
+        assert!(!a.is_poisoned());
+        assert!(a.is_empty());
+        // Overflowing the capacity one element at a time hits the same lock
+        assert!(catch_unwind(AssertUnwindSafe(|| a.extend((0..3).filter(|_| true)))).is_err());
+        assert!(!a.is_poisoned());
+        assert_eq!(a, [0, 1]);
+    }
+
+    struct Tracked<'a> {
+        drops: &'a Cell<usize>,
+        panic_on_drop: bool,
+        panic_on_clone: bool,
+    }
+
+    impl<'a> Tracked<'a> {
+        fn new(drops: &'a Cell<usize>) -> Self {
+            Tracked {
+                drops,
+                panic_on_drop: false,
+                panic_on_clone: false,
+            }
+        }
+    }
+
+    impl Clone for Tracked<'_> {
+        fn clone(&self) -> Self {
+            assert!(!self.panic_on_clone, "clone");
+            Tracked::new(self.drops)
+        }
+    }
+
+    impl Drop for Tracked<'_> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panic_on_drop {
+                panic!("drop");
+            }
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let mut a: Vec<usize> = (0..5).collect();
+        a.extend((5..100).filter(|x| x % 2 == 0));
+        a.extend(std::vec![100, 101]);
+        assert_eq!(a.len(), 5 + 47 + 2);
+        assert_eq!(a[53], 101);
+    }
+
+    #[test]
+    fn extend_panic() {
+        let drops = Cell::new(0);
+        let mut a = Vec::new();
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            a.extend((0..10).map(|i| {
+                assert!(i != 7);
+                Tracked::new(&drops)
+            }))
+        }));
+        assert!(res.is_err());
+        assert!(a.is_poisoned());
+        a.clear_poison();
+        assert_eq!(a.len(), 7);
+        drop(a);
+        assert_eq!(drops.get(), 7);
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Cell::new(0);
+        let mut a = Vec::new();
+        for i in 0..10 {
+            let mut t = Tracked::new(&drops);
+            t.panic_on_drop = i == 6;
+            a.push(t);
+        }
+        assert!(catch_unwind(AssertUnwindSafe(|| a.truncate(3))).is_err());
+        assert_eq!(drops.get(), 7);
+        assert!(a.is_poisoned());
+        // Dropping a poisoned vector must not panic again
+        drop(a);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn drop_order_and_panic() {
+        let order = core::cell::RefCell::new(std::vec::Vec::new());
+        struct Logged<'a>(usize, &'a core::cell::RefCell<std::vec::Vec<usize>>);
+        impl Drop for Logged<'_> {
+            fn drop(&mut self) {
+                self.1.borrow_mut().push(self.0);
+                if self.0 == 1 {
+                    panic!("drop");
+                }
+            }
+        }
+        let a: Vec<_> = (0..4).map(|i| Logged(i, &order)).collect();
+        assert!(catch_unwind(AssertUnwindSafe(|| drop(a))).is_err());
+        // Every element is dropped despite the panic, in forward order
+        assert_eq!(*order.borrow(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn from_fn() {
+        let a = Vec::from_fn(5, |i| i * i);
+        assert_eq!(a, [0, 1, 4, 9, 16]);
+        assert_eq!(a.capacity(), 5);
+        assert!(Vec::<u8>::from_fn(0, |_| unreachable!()).is_empty());
+
+        let drops = Cell::new(0);
+        let r = catch_unwind(AssertUnwindSafe(|| {
+            Vec::from_fn(6, |i| {
+                assert!(i < 4, "f");
+                Tracked::new(&drops)
+            })
+        }));
+        assert!(r.is_err());
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn from_iter_exact() {
+        let a = Vec::from_iter_exact(3, 0..3).unwrap();
+        assert_eq!((a.len(), a.capacity()), (3, 3));
+        assert_eq!(
+            Vec::from_iter_exact(3, 0..2),
+            Err(LengthError::TooFew {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            Vec::from_iter_exact(3, 0..4),
+            Err(LengthError::TooMany { expected: 3 })
+        );
+        // A lying size hint changes nothing
+        let liar = (0..5).filter(|_| true);
+        assert!(Vec::from_iter_exact(5, liar).is_ok());
+
+        let zst = Vec::from_iter_exact(3, core::iter::repeat_n((), 3)).unwrap();
+        assert_eq!(zst.len(), 3);
+        assert_eq!(
+            Vec::from_iter_exact(3, core::iter::repeat_n((), 4)).map(|v| v.len()),
+            Err(LengthError::TooMany { expected: 3 })
+        );
+        assert!(Vec::from_iter_exact(3, core::iter::repeat(())).is_err());
+    }
+
+    #[test]
+    fn clone_panic() {
+        let drops = Cell::new(0);
+        let mut a = Vec::new();
+        for i in 0..5 {
+            let mut t = Tracked::new(&drops);
+            t.panic_on_clone = i == 3;
+            a.push(t);
+        }
+        assert!(catch_unwind(AssertUnwindSafe(|| a.gather(&[0, 1, 2, 3, 4]))).is_err());
+        // The three clones made before the panic were dropped with the partial result
+        assert_eq!(drops.get(), 3);
+        drop(a);
+        assert_eq!(drops.get(), 8);
+    }
+
+    #[test]
+    fn checked_capacity() {
+        fn grown<T>(cap: usize, len: usize, additional: usize, amortized: bool) -> usize {
+            super::checked_capacity::<T>(cap, len, additional, amortized, usize::MAX)
+                .unwrap()
+                .0
+        }
+        assert_eq!(grown::<u64>(4, 4, 1, true), 8);
+        assert_eq!(grown::<u64>(4, 4, 9, true), 13);
+        assert_eq!(grown::<u64>(4, 4, 1, false), 5);
+        assert_eq!(grown::<u8>(0, 0, 1, true), 8);
+        assert_eq!(grown::<u64>(0, 0, 1, true), 4);
+        assert_eq!(grown::<[u8; 2048]>(0, 0, 1, true), 1);
+        assert_eq!(grown::<u64>(0, 0, 1, false), 1);
+        assert_eq!(
+            super::checked_capacity::<u64>(0, usize::MAX, 1, false, usize::MAX),
+            Err(CapacityError)
+        );
+        assert_eq!(
+            super::checked_capacity::<u64>(0, isize::MAX as usize / 8 + 1, 0, false, usize::MAX),
+            Err(CapacityError)
+        );
+        // Doubling stops at the limit, and reserving past it fails
+        assert_eq!(
+            super::checked_capacity::<u64>(8, 8, 1, true, 10).unwrap().0,
+            10
+        );
+        assert_eq!(
+            super::checked_capacity::<u64>(8, 8, 3, true, 10),
+            Err(CapacityError)
+        );
     }
 }
@@ -0,0 +1,189 @@
+// Stress-test and demo driver for the library:
+//
+//     rust_vec stress [--seed N] [--iterations N] [--ops N]
+//     rust_vec alloc-stats [--n N]
+//     rust_vec bench [--n N]
+#[cfg(not(feature = "no_global_oom_handling"))]
+mod cli {
+    use rust_vec::model::{self, LeakCheckAlloc, Op};
+    use rust_vec::MemoryReport;
+    use std::hint::black_box;
+    use std::process;
+    use std::time::Instant;
+
+    #[global_allocator]
+    static ALLOC: LeakCheckAlloc = LeakCheckAlloc;
+
+    const USAGE: &str =
+        "usage: rust_vec <stress|alloc-stats|bench> [--seed N] [--iterations N] [--ops N] [--n N]";
+
+    struct Options {
+        seed: u64,
+        iterations: usize,
+        ops: usize,
+        n: usize,
+    }
+
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut opts = Options {
+            seed: 1,
+            iterations: 1000,
+            ops: 200,
+            n: 100_000,
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for {}", flag))?;
+            let value: u64 = value
+                .parse()
+                .map_err(|_| format!("invalid value for {}: {}", flag, value))?;
+            match flag.as_str() {
+                "--seed" => opts.seed = value.max(1),
+                "--iterations" => opts.iterations = value as usize,
+                "--ops" => opts.ops = value as usize,
+                "--n" => opts.n = value as usize,
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        Ok(opts)
+    }
+
+    // xorshift64; any non-zero seed works.
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    // Runs random operation sequences against std's Vec; `model::run` panics on the first
+    // divergence or leak, and the seed printed here reproduces it.
+    fn stress(opts: &Options) {
+        let mut state = opts.seed;
+        for i in 0..opts.iterations {
+            let bytes: Vec<u8> = (0..opts.ops * 3).map(|_| next(&mut state) as u8).collect();
+            model::run(&Op::decode(&bytes));
+            if (i + 1) % 100 == 0 {
+                println!("{} sequences ok", i + 1);
+            }
+        }
+        println!(
+            "stress: {} sequences of {} ops passed (seed {})",
+            opts.iterations, opts.ops, opts.seed
+        );
+    }
+
+    fn alloc_stats(opts: &Options) {
+        // Printing allocates stdout's buffer, so do it once before taking the baseline
+        println!("alloc-stats");
+        let base = LeakCheckAlloc::live_bytes();
+        let mut v = rust_vec::Vec::new();
+        let mut reallocs = 0;
+        for i in 0..opts.n as u64 {
+            let cap = v.capacity();
+            v.push(i);
+            reallocs += (v.capacity() != cap) as usize;
+        }
+        println!("after {} pushes of u64:", opts.n);
+        println!("  capacity        {}", v.capacity());
+        println!("  reallocations   {}", reallocs);
+        println!("  allocated bytes {}", v.allocated_bytes());
+        println!("  used bytes      {}", v.used_bytes());
+        println!("  spare bytes     {}", v.spare_capacity_bytes());
+        println!("  live heap delta {}", LeakCheckAlloc::live_bytes() - base);
+        v.shrink_to_fit();
+        println!("after shrink_to_fit:");
+        println!("  allocated bytes {}", v.allocated_bytes());
+        drop(v);
+        println!("after drop:");
+        println!("  live heap delta {}", LeakCheckAlloc::live_bytes() - base);
+    }
+
+    fn time(name: &str, f: impl Fn()) {
+        const ROUNDS: u32 = 10;
+        f();
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            f();
+        }
+        println!("  {:<24} {:?}", name, start.elapsed() / ROUNDS);
+    }
+
+    fn bench(opts: &Options) {
+        let n = opts.n;
+        println!("mean over 10 rounds, n = {}:", n);
+        time("push rust_vec", || {
+            let mut v = rust_vec::Vec::new();
+            for i in 0..n {
+                v.push(black_box(i));
+            }
+            black_box(v);
+        });
+        time("push std", || {
+            let mut v = Vec::new();
+            for i in 0..n {
+                v.push(black_box(i));
+            }
+            black_box(v);
+        });
+        time("extend rust_vec", || {
+            let mut v = rust_vec::Vec::new();
+            v.extend(black_box(0..n));
+            black_box(v);
+        });
+        time("extend std", || {
+            let mut v = Vec::new();
+            v.extend(black_box(0..n));
+            black_box(v);
+        });
+        let m = n.min(10_000);
+        time("insert front rust_vec", || {
+            let mut v = rust_vec::Vec::new();
+            for i in 0..m {
+                v.insert(0, black_box(i));
+            }
+            black_box(v);
+        });
+        time("insert front std", || {
+            let mut v = Vec::new();
+            for i in 0..m {
+                v.insert(0, black_box(i));
+            }
+            black_box(v);
+        });
+    }
+
+    pub fn main() {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let (cmd, rest) = match args.split_first() {
+            Some((cmd, rest)) => (cmd.as_str(), rest),
+            None => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        };
+        let opts = parse(rest).unwrap_or_else(|e| {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        });
+        match cmd {
+            "stress" => stress(&opts),
+            "alloc-stats" => alloc_stats(&opts),
+            "bench" => bench(&opts),
+            _ => {
+                eprintln!("unknown command {}\n{}", cmd, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+fn main() {
+    cli::main()
+}
+
+#[cfg(feature = "no_global_oom_handling")]
+fn main() {}
@@ -0,0 +1,125 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::mem;
+use metrics::{counter, histogram};
+
+pub(crate) const DEFAULT_FAMILY: &str = "default";
+
+// Every allocation change is labelled with the vector's family. The capacity histogram's max is
+// the family's peak capacity.
+pub(crate) fn record_realloc<T>(family: &'static str, old_cap: usize, new_cap: usize) {
+    let size = mem::size_of::<T>();
+    if new_cap == 0 {
+        return;
+    }
+    if old_cap == 0 {
+        counter!("rust_vec_allocations_total", "family" => family).increment(1);
+    } else {
+        counter!("rust_vec_reallocations_total", "family" => family).increment(1);
+    }
+    if new_cap > old_cap {
+        counter!("rust_vec_allocated_bytes_total", "family" => family)
+            .increment(((new_cap - old_cap) * size) as u64);
+    }
+    histogram!("rust_vec_capacity_bytes", "family" => family).record((new_cap * size) as f64);
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn with_metrics_family(mut self, family: &'static str) -> Self {
+        self.buf.family = family;
+        self
+    }
+
+    pub fn set_metrics_family(&mut self, family: &'static str) {
+        self.buf.family = family;
+    }
+
+    pub fn metrics_family(&self) -> &'static str {
+        self.buf.family
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use metrics::{
+        Counter, CounterFn, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+        SharedString, Unit,
+    };
+    use std::collections::BTreeMap;
+    use std::string::{String, ToString};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Max(AtomicU64);
+
+    impl HistogramFn for Max {
+        fn record(&self, value: f64) {
+            self.0.fetch_max(value as u64, Ordering::Relaxed);
+        }
+    }
+
+    impl CounterFn for Max {
+        fn increment(&self, value: u64) {
+            self.0.fetch_add(value, Ordering::Relaxed);
+        }
+        fn absolute(&self, value: u64) {
+            self.0.store(value, Ordering::Relaxed);
+        }
+    }
+
+    // Keeps one value per "name/family": a sum for counters and the max for histograms.
+    #[derive(Default)]
+    struct TestRecorder(Mutex<BTreeMap<String, Arc<Max>>>);
+
+    impl TestRecorder {
+        fn slot(&self, key: &Key) -> Arc<Max> {
+            let family = key.labels().next().unwrap().value().to_string();
+            let name = std::format!("{}/{}", key.name(), family);
+            self.0.lock().unwrap().entry(name).or_default().clone()
+        }
+
+        fn get(&self, name: &str) -> u64 {
+            self.0.lock().unwrap()[name].0.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.slot(key))
+        }
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.slot(key))
+        }
+    }
+
+    #[test]
+    fn records_per_family() {
+        let recorder = TestRecorder::default();
+        metrics::with_local_recorder(&recorder, || {
+            let mut a = Vec::<u32>::new().with_metrics_family("parser");
+            for i in 0..5 {
+                a.push(i);
+            }
+            a.shrink_to_fit();
+            let mut b = Vec::<u64>::with_capacity(2);
+            b.push(1);
+            assert_eq!(a.metrics_family(), "parser");
+            assert_eq!(b.metrics_family(), DEFAULT_FAMILY);
+        });
+        // Capacities 4, 8, then 5.
+        assert_eq!(recorder.get("rust_vec_allocations_total/parser"), 1);
+        assert_eq!(recorder.get("rust_vec_reallocations_total/parser"), 2);
+        assert_eq!(recorder.get("rust_vec_allocated_bytes_total/parser"), 32);
+        assert_eq!(recorder.get("rust_vec_capacity_bytes/parser"), 32);
+        assert_eq!(recorder.get("rust_vec_allocations_total/default"), 1);
+        assert_eq!(recorder.get("rust_vec_capacity_bytes/default"), 16);
+    }
+}
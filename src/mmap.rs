@@ -0,0 +1,146 @@
+// Anonymous memory mappings for the allocators that manage pages themselves. Each allocator
+// feature uses a different subset of these, so with only some of them enabled the rest are
+// unused.
+#![allow(dead_code)]
+use core::alloc::AllocError;
+use core::ptr::{self, NonNull};
+
+pub(crate) fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+pub(crate) fn round_up(n: usize, to: usize) -> Option<usize> {
+    n.checked_next_multiple_of(to)
+}
+
+// Maps `len` bytes of zeroed read-write memory starting at a multiple of `align`, which must be
+// a power of two no smaller than the page size. Over-maps by `align` and unmaps the slack on
+// both sides.
+pub(crate) fn map_aligned(len: usize, align: usize) -> Result<NonNull<u8>, AllocError> {
+    let total = len.checked_add(align).ok_or(AllocError)?;
+    unsafe {
+        let raw = libc::mmap(
+            ptr::null_mut(),
+            total,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if raw == libc::MAP_FAILED {
+            return Err(AllocError);
+        }
+        let start = raw as usize;
+        let aligned = (start + align - 1) & !(align - 1);
+        let head = aligned - start;
+        if head != 0 {
+            libc::munmap(raw, head);
+        }
+        let tail = total - head - len;
+        if tail != 0 {
+            libc::munmap((aligned + len) as *mut libc::c_void, tail);
+        }
+        Ok(NonNull::new_unchecked(aligned as *mut u8))
+    }
+}
+
+// Reserves `len` bytes of address space without backing memory: the range is inaccessible and
+// not charged against the commit limit until parts of it are committed.
+pub(crate) fn reserve(len: usize) -> Result<NonNull<u8>, AllocError> {
+    unsafe {
+        let raw = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+            -1,
+            0,
+        );
+        if raw == libc::MAP_FAILED {
+            return Err(AllocError);
+        }
+        Ok(NonNull::new_unchecked(raw as *mut u8))
+    }
+}
+
+// Makes `len` bytes at `addr` of a reserved range readable and writable; both must be page
+// aligned.
+pub(crate) unsafe fn commit(addr: usize, len: usize) -> Result<(), AllocError> {
+    let prot = libc::PROT_READ | libc::PROT_WRITE;
+    if libc::mprotect(addr as *mut libc::c_void, len, prot) != 0 {
+        return Err(AllocError);
+    }
+    Ok(())
+}
+
+pub(crate) unsafe fn unmap(ptr: NonNull<u8>, len: usize) {
+    libc::munmap(ptr.as_ptr() as *mut libc::c_void, len);
+}
+
+// Makes `len` bytes at `addr` inaccessible; both must be page aligned.
+pub(crate) unsafe fn protect_none(addr: usize, len: usize) {
+    libc::mprotect(addr as *mut libc::c_void, len, libc::PROT_NONE);
+}
+
+// Resizes a mapping made by `map_aligned`, moving it if it can't grow in place.
+pub(crate) unsafe fn remap(
+    ptr: NonNull<u8>,
+    old_len: usize,
+    new_len: usize,
+) -> Result<NonNull<u8>, AllocError> {
+    let raw = libc::mremap(
+        ptr.as_ptr() as *mut libc::c_void,
+        old_len,
+        new_len,
+        libc::MREMAP_MAYMOVE,
+    );
+    if raw == libc::MAP_FAILED {
+        Err(AllocError)
+    } else {
+        Ok(NonNull::new_unchecked(raw as *mut u8))
+    }
+}
+
+// Grows or shrinks a mapping without moving it.
+pub(crate) unsafe fn remap_in_place(
+    ptr: NonNull<u8>,
+    old_len: usize,
+    new_len: usize,
+) -> Result<(), AllocError> {
+    let raw = libc::mremap(ptr.as_ptr() as *mut libc::c_void, old_len, new_len, 0);
+    if raw == libc::MAP_FAILED {
+        Err(AllocError)
+    } else {
+        Ok(())
+    }
+}
+
+// Moves the pages of a mapping to `dst`, replacing the `new_len` bytes mapped there, and resizes
+// it to `new_len`. Pages are moved, not copied. On failure both mappings are left as they were.
+pub(crate) unsafe fn remap_to(
+    ptr: NonNull<u8>,
+    old_len: usize,
+    new_len: usize,
+    dst: NonNull<u8>,
+) -> Result<(), AllocError> {
+    let raw = libc::mremap(
+        ptr.as_ptr() as *mut libc::c_void,
+        old_len,
+        new_len,
+        libc::MREMAP_MAYMOVE | libc::MREMAP_FIXED,
+        dst.as_ptr() as *mut libc::c_void,
+    );
+    if raw == libc::MAP_FAILED {
+        Err(AllocError)
+    } else {
+        Ok(())
+    }
+}
+
+// Failures are ignored: advice that the kernel doesn't support or refuses changes nothing about
+// correctness.
+pub(crate) unsafe fn advise(ptr: *mut u8, len: usize, advice: libc::c_int) {
+    if len != 0 {
+        libc::madvise(ptr as *mut libc::c_void, len, advice);
+    }
+}
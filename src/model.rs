@@ -0,0 +1,190 @@
+//! Differential testing against `std::vec::Vec`.
+//!
+//! `run` applies a sequence of operations to this crate's `Vec` and to std's, checking after
+//! each step that both hold the same elements. Elements are boxed so that a missed or doubled
+//! drop shows up as a leak or a double free; install `LeakCheckAlloc` as the global allocator
+//! (as the fuzz target does) to also have `run` assert that every byte was freed.
+
+use crate::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::alloc::System;
+use std::boxed::Box;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Push(u32),
+    Pop,
+    Insert(usize, u32),
+    Remove(usize),
+    SwapRemove(usize),
+    Truncate(usize),
+    Reserve(usize),
+    ShrinkToFit,
+    Drain(usize),
+    Extend(u32, usize),
+    Clear,
+}
+
+impl Op {
+    // Decodes three bytes per op; a trailing partial op is ignored. Indices are taken modulo
+    // the current length by `run`, so every decoded sequence is valid.
+    pub fn decode(data: &[u8]) -> std::vec::Vec<Op> {
+        data.chunks_exact(3)
+            .map(|c| {
+                let (a, b) = (c[1] as usize, c[2] as u32);
+                match c[0] % 11 {
+                    0 => Op::Push(b),
+                    1 => Op::Pop,
+                    2 => Op::Insert(a, b),
+                    3 => Op::Remove(a),
+                    4 => Op::SwapRemove(a),
+                    5 => Op::Truncate(a),
+                    6 => Op::Reserve(a),
+                    7 => Op::ShrinkToFit,
+                    8 => Op::Drain(a),
+                    9 => Op::Extend(b, a % 16),
+                    _ => Op::Clear,
+                }
+            })
+            .collect()
+    }
+}
+
+pub fn run(ops: &[Op]) {
+    let before = LeakCheckAlloc::live_bytes();
+    {
+        let mut ours: Vec<Box<u32>> = Vec::new();
+        let mut std: std::vec::Vec<Box<u32>> = std::vec::Vec::new();
+        for &op in ops {
+            apply(op, &mut ours, &mut std);
+            assert_eq!(&ours[..], &std[..], "diverged after {:?}", op);
+            assert!(ours.capacity() >= ours.len());
+        }
+    }
+    if LeakCheckAlloc::installed() {
+        assert_eq!(
+            LeakCheckAlloc::live_bytes(),
+            before,
+            "leaked after {:?}",
+            ops
+        );
+    }
+}
+
+// The boxes are the point: they make drop bugs visible.
+#[allow(clippy::vec_box)]
+fn apply(op: Op, ours: &mut Vec<Box<u32>>, std: &mut std::vec::Vec<Box<u32>>) {
+    let len = std.len();
+    match op {
+        Op::Push(x) => {
+            ours.push(Box::new(x));
+            std.push(Box::new(x));
+        }
+        Op::Pop => assert_eq!(ours.pop(), std.pop()),
+        Op::Insert(i, x) => {
+            ours.insert(i % (len + 1), Box::new(x));
+            std.insert(i % (len + 1), Box::new(x));
+        }
+        Op::Remove(i) if len > 0 => assert_eq!(ours.remove(i % len), std.remove(i % len)),
+        Op::SwapRemove(i) if len > 0 => {
+            assert_eq!(ours.swap_remove(i % len), std.swap_remove(i % len))
+        }
+        Op::Remove(_) | Op::SwapRemove(_) => {}
+        Op::Truncate(n) => {
+            ours.truncate(n);
+            std.truncate(n);
+        }
+        Op::Reserve(n) => {
+            ours.reserve(n);
+            std.reserve(n);
+            assert!(ours.capacity() >= len + n);
+        }
+        Op::ShrinkToFit => {
+            ours.shrink_to_fit();
+            std.shrink_to_fit();
+        }
+        // Our `drain` empties the whole vector; only take `n` items and let the rest drop with it.
+        Op::Drain(n) => {
+            let taken: std::vec::Vec<_> = ours.drain().take(n).collect();
+            let expected: std::vec::Vec<_> = std.drain(..).take(n).collect();
+            assert_eq!(taken, expected);
+        }
+        Op::Extend(x, n) => {
+            ours.extend((0..n as u32).map(|i| Box::new(x + i)));
+            std.extend((0..n as u32).map(|i| Box::new(x + i)));
+        }
+        Op::Clear => {
+            ours.clear();
+            std.clear();
+        }
+    }
+}
+
+static LIVE_BYTES: AtomicIsize = AtomicIsize::new(0);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+// A `System` wrapper that tracks live heap bytes. Only meaningful when installed as the
+// `#[global_allocator]` of a single-threaded binary.
+pub struct LeakCheckAlloc;
+
+impl LeakCheckAlloc {
+    pub fn live_bytes() -> isize {
+        LIVE_BYTES.load(Ordering::SeqCst)
+    }
+
+    fn installed() -> bool {
+        INSTALLED.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for LeakCheckAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(layout.size() as isize, Ordering::SeqCst);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            LIVE_BYTES.fetch_add(new_size as isize - layout.size() as isize, Ordering::SeqCst);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_sequences() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for _ in 0..200 {
+            let bytes: std::vec::Vec<u8> = (0..300)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect();
+            run(&Op::decode(&bytes));
+        }
+    }
+
+    #[test]
+    fn decode_is_total() {
+        assert!(Op::decode(&[0, 1]).is_empty());
+        assert_eq!(
+            Op::decode(&[0, 1, 2, 3, 5, 0]),
+            [Op::Push(2), Op::Remove(5)]
+        );
+    }
+}
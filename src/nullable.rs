@@ -0,0 +1,200 @@
+use crate::{MemoryReport, Vec};
+use core::mem::MaybeUninit;
+
+// A vector of optional values laid out like an Arrow array: one value slot per element plus a
+// validity bitmap with bit `i % 8` of byte `i / 8` set when element `i` is present. Null slots
+// are zeroed rather than left uninitialized so the value buffer can be handed out as bytes.
+pub struct NullableVec<T> {
+    values: Vec<MaybeUninit<T>>,
+    validity: Vec<u8>,
+    nulls: usize,
+}
+
+impl<T> NullableVec<T> {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            validity: Vec::new(),
+            nulls: 0,
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(cap),
+            validity: Vec::with_capacity(cap.div_ceil(8)),
+            nulls: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.nulls
+    }
+
+    pub fn is_valid(&self, index: usize) -> bool {
+        index < self.len() && self.validity[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn push(&mut self, value: Option<T>) {
+        let index = self.len();
+        if index.is_multiple_of(8) {
+            self.validity.push(0);
+        }
+        match value {
+            Some(v) => {
+                self.values.push(MaybeUninit::new(v));
+                self.validity[index / 8] |= 1 << (index % 8);
+            }
+            None => {
+                self.values.push(MaybeUninit::zeroed());
+                self.nulls += 1;
+            }
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Option<T>> {
+        let index = self.len().checked_sub(1)?;
+        let valid = self.is_valid(index);
+        let slot = self.values.pop().unwrap();
+        self.validity[index / 8] &= !(1 << (index % 8));
+        if index.is_multiple_of(8) {
+            self.validity.pop();
+        }
+        if valid {
+            Some(Some(unsafe { slot.assume_init() }))
+        } else {
+            self.nulls -= 1;
+            Some(None)
+        }
+    }
+
+    // Returns `None` both for null elements and for out-of-bounds indices.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if self.is_valid(index) {
+            Some(unsafe { self.values[index].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.is_valid(index) {
+            Some(unsafe { self.values[index].assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<&T>> + '_ {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn validity_bitmap(&self) -> &[u8] {
+        &self.validity
+    }
+
+    #[cfg(feature = "arrow")]
+    pub(crate) fn into_parts(self) -> (Vec<MaybeUninit<T>>, Vec<u8>, usize) {
+        let this = core::mem::ManuallyDrop::new(self);
+        unsafe {
+            (
+                core::ptr::read(&this.values),
+                core::ptr::read(&this.validity),
+                this.nulls,
+            )
+        }
+    }
+}
+
+impl<T> Drop for NullableVec<T> {
+    fn drop(&mut self) {
+        if core::mem::needs_drop::<T>() {
+            self.clear();
+        }
+    }
+}
+
+impl<T> Default for NullableVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<Option<T>> for NullableVec<T> {
+    fn extend<I: IntoIterator<Item = Option<T>>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> core::iter::FromIterator<Option<T>> for NullableVec<T> {
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T> MemoryReport for NullableVec<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.values.allocated_bytes() + self.validity.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.values.used_bytes() + self.validity.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    #[test]
+    fn push_get_iter() {
+        let mut v: NullableVec<u32> = (0..20).map(|i| (i % 3 != 0).then_some(i)).collect();
+        assert_eq!(v.len(), 20);
+        assert_eq!(v.null_count(), 7);
+        assert_eq!(v.get(0), None);
+        assert_eq!(v.get(1), Some(&1));
+        assert_eq!(v.get(20), None);
+        assert_eq!(
+            v.validity_bitmap(),
+            &[0b1011_0110, 0b0110_1101, 0b0000_1011]
+        );
+        *v.get_mut(2).unwrap() = 200;
+        assert!(v
+            .iter()
+            .map(|x| x.copied())
+            .eq((0..20).map(|i| (i % 3 != 0).then_some(if i == 2 { 200 } else { i }))));
+
+        assert_eq!(v.pop(), Some(Some(19)));
+        assert_eq!(v.pop(), Some(None));
+        assert_eq!(v.pop(), Some(Some(17)));
+        assert_eq!(v.null_count(), 6);
+    }
+
+    #[test]
+    fn drops_only_valid_values() {
+        let mut v = NullableVec::new();
+        v.push(Some(Box::new(1)));
+        v.push(None);
+        v.push(Some(Box::new(3)));
+        assert_eq!(v.pop(), Some(Some(Box::new(3))));
+        assert_eq!(v.null_count(), 1);
+        drop(v);
+    }
+}
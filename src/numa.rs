@@ -0,0 +1,230 @@
+use crate::{mmap, Vec};
+use core::alloc::{AllocError, Allocator, Layout};
+use core::ptr::NonNull;
+use std::io;
+
+// `mbind` modes and `get_mempolicy` flags from <linux/mempolicy.h>.
+const MPOL_PREFERRED: libc::c_int = 1;
+const MPOL_BIND: libc::c_int = 2;
+const MPOL_INTERLEAVE: libc::c_int = 3;
+const MPOL_F_NODE: libc::c_int = 1;
+const MPOL_F_ADDR: libc::c_int = 2;
+
+// Where a `NumaAlloc` places pages. Node ids must be below 64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumaPolicy {
+    // Only ever allocate on this node.
+    Bind(usize),
+    // Prefer this node, falling back to others when it is full.
+    Preferred(usize),
+    // Spread pages round-robin over the nodes set in the mask.
+    Interleave(u64),
+}
+
+impl NumaPolicy {
+    fn mode_and_mask(self) -> (libc::c_int, u64) {
+        match self {
+            NumaPolicy::Bind(node) => (MPOL_BIND, 1 << node),
+            NumaPolicy::Preferred(node) => (MPOL_PREFERRED, 1 << node),
+            NumaPolicy::Interleave(mask) => (MPOL_INTERLEAVE, mask),
+        }
+    }
+}
+
+// Gives every buffer its own page-aligned mapping and applies the policy to it with `mbind`
+// before any page is touched. Even small buffers take whole pages, so this is meant for the
+// large, long-lived vectors whose placement matters. A policy the kernel rejects (e.g. a node
+// that doesn't exist) fails the allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumaAlloc {
+    policy: NumaPolicy,
+}
+
+impl NumaAlloc {
+    pub fn new(policy: NumaPolicy) -> Self {
+        assert!(
+            match policy {
+                NumaPolicy::Bind(n) | NumaPolicy::Preferred(n) => n < 64,
+                NumaPolicy::Interleave(mask) => mask != 0,
+            },
+            "invalid NUMA policy {:?}",
+            policy
+        );
+        Self { policy }
+    }
+
+    pub fn policy(&self) -> NumaPolicy {
+        self.policy
+    }
+
+    fn bind(&self, ptr: NonNull<u8>, len: usize) -> Result<(), AllocError> {
+        let (mode, mask) = self.policy.mode_and_mask();
+        // The kernel reads `maxnode - 1` bits
+        let r = unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                ptr.as_ptr(),
+                len,
+                mode,
+                &mask as *const u64,
+                u64::BITS as libc::c_ulong + 1,
+                0 as libc::c_uint,
+            )
+        };
+        if r == 0 {
+            Ok(())
+        } else {
+            Err(AllocError)
+        }
+    }
+}
+
+fn mapped_len(size: usize) -> Result<usize, AllocError> {
+    mmap::round_up(size, mmap::page_size()).ok_or(AllocError)
+}
+
+unsafe impl Allocator for NumaAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let len = mapped_len(layout.size().max(1))?;
+        let ptr = mmap::map_aligned(len, layout.align().max(mmap::page_size()))?;
+        if let Err(e) = self.bind(ptr, len) {
+            unsafe { mmap::unmap(ptr, len) };
+            return Err(e);
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        mmap::unmap(ptr, mapped_len(layout.size().max(1)).unwrap());
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_len, new_len) = (
+            mapped_len(old_layout.size().max(1))?,
+            mapped_len(new_layout.size())?,
+        );
+        if new_len == old_len {
+            return Ok(NonNull::slice_from_raw_parts(ptr, old_len));
+        }
+        if new_layout.align() > mmap::page_size() {
+            // `mremap` may move the mapping to any page boundary
+            let new = self.allocate(new_layout)?;
+            core::ptr::copy_nonoverlapping(
+                ptr.as_ptr(),
+                new.cast::<u8>().as_ptr(),
+                old_layout.size(),
+            );
+            self.deallocate(ptr, old_layout);
+            return Ok(new);
+        }
+        let new = mmap::remap(ptr, old_len, new_len)?;
+        // The old mapping is gone, so this can no longer fail the call. The moved pages keep
+        // their policy; if binding the added ones fails they fall back to the default policy.
+        let added = NonNull::new_unchecked(new.as_ptr().add(old_len));
+        let _ = self.bind(added, new_len - old_len);
+        Ok(NonNull::slice_from_raw_parts(new, new_len))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_len, new_len) = (
+            mapped_len(old_layout.size().max(1))?,
+            mapped_len(new_layout.size().max(1))?,
+        );
+        if new_len < old_len {
+            mmap::unmap(
+                NonNull::new_unchecked(ptr.as_ptr().add(new_len)),
+                old_len - new_len,
+            );
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_len))
+    }
+}
+
+// The node holding the page that `value` starts on, faulting the page in first if it isn't yet.
+pub fn node_of<T: ?Sized>(value: &T) -> io::Result<usize> {
+    node_at(value as *const T as *const u8 as usize)
+}
+
+fn node_at(addr: usize) -> io::Result<usize> {
+    let mut node: libc::c_int = -1;
+    let r = unsafe {
+        libc::syscall(
+            libc::SYS_get_mempolicy,
+            &mut node as *mut libc::c_int,
+            core::ptr::null_mut::<libc::c_ulong>(),
+            0 as libc::c_ulong,
+            addr as libc::c_ulong,
+            MPOL_F_NODE | MPOL_F_ADDR,
+        )
+    };
+    if r == 0 {
+        Ok(node as usize)
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    // Counts the pages under the elements per node: `placement[n]` is the number of pages on node
+    // `n`.
+    pub fn numa_placement(&self) -> io::Result<Vec<usize>> {
+        let mut placement = Vec::new();
+        let bytes = self.len * core::mem::size_of::<T>();
+        if bytes == 0 {
+            return Ok(placement);
+        }
+        let page = mmap::page_size();
+        let start = self.buf.ptr.as_ptr() as usize;
+        let mut addr = start & !(page - 1);
+        while addr < start + bytes {
+            let node = node_at(addr)?;
+            while placement.len() <= node {
+                placement.push(0);
+            }
+            placement[node] += 1;
+            addr += page;
+        }
+        Ok(placement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_to_node_zero() {
+        let alloc = NumaAlloc::new(NumaPolicy::Bind(0));
+        let mut v = Vec::new_in(alloc);
+        for i in 0..100_000u64 {
+            v.push(i);
+        }
+        assert_eq!(v.allocator().policy(), NumaPolicy::Bind(0));
+        assert_eq!(v.as_ptr() as usize % mmap::page_size(), 0);
+        let placement = v.numa_placement().unwrap();
+        let pages = (v.len() * 8).div_ceil(mmap::page_size());
+        assert_eq!(&placement[..], &[pages]);
+        assert_eq!(node_of(&v[5]).unwrap(), 0);
+
+        v.truncate(10);
+        v.shrink_to_fit();
+        assert!(v.iter().copied().eq(0..10));
+    }
+
+    #[test]
+    fn missing_node_fails() {
+        let alloc = NumaAlloc::new(NumaPolicy::Bind(63));
+        let mut v = Vec::<u8, _>::new_in(alloc);
+        assert!(v.try_reserve(1).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{MemoryReport, Vec};
+use alloc::boxed::Box;
+use core::ops::Deref;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Insert { index: usize, count: usize },
+    Remove { index: usize, count: usize },
+    Set { index: usize },
+    Clear,
+}
+
+type Listener = Box<dyn FnMut(&Change)>;
+
+// A vector that reports every mutation to its listeners. Reads go through `Deref<Target = [T]>`;
+// there is deliberately no `DerefMut`, since writes through a slice would go unnoticed.
+pub struct ObservableVec<T> {
+    vec: Vec<T>,
+    listeners: Vec<Listener>,
+}
+
+impl<T> ObservableVec<T> {
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+
+    pub fn subscribe<F: FnMut(&Change) + 'static>(&mut self, listener: F) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    fn emit(&mut self, change: Change) {
+        for listener in self.listeners.iter_mut() {
+            listener(&change);
+        }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.vec.push(elem);
+        let index = self.vec.len() - 1;
+        self.emit(Change::Insert { index, count: 1 });
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let elem = self.vec.pop()?;
+        let index = self.vec.len();
+        self.emit(Change::Remove { index, count: 1 });
+        Some(elem)
+    }
+
+    pub fn insert(&mut self, index: usize, elem: T) {
+        self.vec.insert(index, elem);
+        self.emit(Change::Insert { index, count: 1 });
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        let elem = self.vec.remove(index);
+        self.emit(Change::Remove { index, count: 1 });
+        elem
+    }
+
+    pub fn set(&mut self, index: usize, elem: T) -> T {
+        let old = core::mem::replace(&mut self.vec[index], elem);
+        self.emit(Change::Set { index });
+        old
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear();
+        self.emit(Change::Clear);
+    }
+
+    pub fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let index = self.vec.len();
+        self.vec.extend(iter);
+        let count = self.vec.len() - index;
+        if count != 0 {
+            self.emit(Change::Insert { index, count });
+        }
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+impl<T> Default for ObservableVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for ObservableVec<T> {
+    fn from(vec: Vec<T>) -> Self {
+        Self {
+            vec,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl<T> Deref for ObservableVec<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> MemoryReport for ObservableVec<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.vec.allocated_bytes() + self.listeners.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.vec.used_bytes() + self.listeners.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    #[test]
+    fn events() {
+        let log = Rc::new(RefCell::new(std::vec::Vec::new()));
+        let mut v = ObservableVec::new();
+        let sink = log.clone();
+        v.subscribe(move |c| sink.borrow_mut().push(*c));
+        v.push(1);
+        v.extend([2, 3, 4]);
+        v.insert(0, 0);
+        assert_eq!(v.set(1, 10), 1);
+        assert_eq!(v.remove(2), 2);
+        assert_eq!(v.pop(), Some(4));
+        assert_eq!(&v[..], &[0, 10, 3]);
+        v.clear();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Change::Insert { index: 0, count: 1 },
+                Change::Insert { index: 1, count: 3 },
+                Change::Insert { index: 0, count: 1 },
+                Change::Set { index: 1 },
+                Change::Remove { index: 2, count: 1 },
+                Change::Remove { index: 3, count: 1 },
+                Change::Clear,
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::cmp::Ordering;
+use rayon::slice::ParallelSliceMut;
+
+// Parallel sorts over the whole vector. These borrow the elements as a slice, so they go through
+// `DerefMut` like any other in-place slice operation.
+impl<T: Send, A: Allocator> Vec<T, A> {
+    pub fn par_sort(&mut self)
+    where
+        T: Ord,
+    {
+        (**self).par_sort();
+    }
+
+    pub fn par_sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        (**self).par_sort_unstable();
+    }
+
+    pub fn par_sort_by<F: Fn(&T, &T) -> Ordering + Sync>(&mut self, cmp: F) {
+        (**self).par_sort_by(cmp);
+    }
+
+    pub fn par_sort_by_key<K: Ord, F: Fn(&T) -> K + Sync>(&mut self, f: F) {
+        (**self).par_sort_by_key(f);
+    }
+
+    pub fn par_sort_unstable_by_key<K: Ord, F: Fn(&T) -> K + Sync>(&mut self, f: F) {
+        (**self).par_sort_unstable_by_key(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+
+    #[test]
+    fn sorts() {
+        let mut s = 5u64;
+        let raw: std::vec::Vec<(u32, usize)> = (0..100_000)
+            .map(|i| {
+                s = s
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((s >> 40) as u32 % 1000, i)
+            })
+            .collect();
+        let mut want = raw.clone();
+        want.sort();
+
+        let mut a: Vec<(u32, usize)> = raw.iter().copied().collect();
+        a.par_sort();
+        assert_eq!(&a[..], &want[..]);
+
+        let mut a: Vec<(u32, usize)> = raw.iter().copied().collect();
+        a.par_sort_unstable();
+        assert_eq!(&a[..], &want[..]);
+
+        // Stable: equal keys keep their original (increasing) second component
+        let mut a: Vec<(u32, usize)> = raw.iter().copied().collect();
+        a.par_sort_by_key(|x| x.0);
+        assert_eq!(&a[..], &want[..]);
+
+        a.par_sort_by(|x, y| y.cmp(x));
+        assert!(a.windows(2).all(|w| w[0] >= w[1]));
+        a.par_sort_unstable_by_key(|x| x.1);
+        assert!(a.iter().enumerate().all(|(i, x)| x.1 == i));
+    }
+}
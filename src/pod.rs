@@ -0,0 +1,127 @@
+use crate::Vec;
+use bytemuck::{Pod, PodCastError};
+use core::mem;
+#[cfg(not(feature = "no_global_oom_handling"))]
+use core::ptr;
+
+// Reuses the allocation of `v` for `B`. This only works when both types share an alignment
+// (the buffer must be freed with the layout it was allocated with) and the byte length and
+// byte capacity both divide evenly into `B`s.
+// The error hands `v` back; it is only over the lint's limit with `shadow` and `metrics` on.
+#[allow(clippy::result_large_err)]
+pub fn try_cast_vec<A: Pod, B: Pod>(v: Vec<A>) -> Result<Vec<B>, (PodCastError, Vec<A>)> {
+    let (size_a, size_b) = (mem::size_of::<A>(), mem::size_of::<B>());
+    if mem::align_of::<A>() != mem::align_of::<B>() {
+        return Err((PodCastError::AlignmentMismatch, v));
+    }
+    if size_a == size_b {
+        return Ok(unsafe { v.try_cast().ok().unwrap() });
+    }
+    if size_a == 0 || size_b == 0 {
+        return Err((PodCastError::SizeMismatch, v));
+    }
+    let (bytes, cap_bytes) = (v.len() * size_a, v.capacity() * size_a);
+    if !bytes.is_multiple_of(size_b) || !cap_bytes.is_multiple_of(size_b) {
+        return Err((PodCastError::OutputSliceWouldHaveSlop, v));
+    }
+    let (ptr, _, _) = v.into_raw_parts();
+    Ok(unsafe { Vec::from_raw_parts(ptr as *mut B, bytes / size_b, cap_bytes / size_b) })
+}
+
+pub fn cast_vec<A: Pod, B: Pod>(v: Vec<A>) -> Vec<B> {
+    match try_cast_vec(v) {
+        Ok(v) => v,
+        Err((e, _)) => panic!("cast_vec failed: {:?}", e),
+    }
+}
+
+// Converts raw bytes into typed elements, reusing the allocation when the layouts allow it and
+// copying into a fresh, correctly aligned buffer otherwise.
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub fn from_bytes<T: Pod>(bytes: Vec<u8>) -> Result<Vec<T>, PodCastError> {
+    let size = mem::size_of::<T>();
+    if size == 0 || !bytes.len().is_multiple_of(size) {
+        return Err(PodCastError::OutputSliceWouldHaveSlop);
+    }
+    match try_cast_vec(bytes) {
+        Ok(v) => Ok(v),
+        Err((_, bytes)) => {
+            let len = bytes.len() / size;
+            let mut v = Vec::<T>::with_capacity(len);
+            unsafe {
+                let dst = v.spare_capacity_mut().as_mut_ptr() as *mut u8;
+                ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+                v.set_len(len);
+            }
+            Ok(v)
+        }
+    }
+}
+
+#[cfg(not(feature = "no_global_oom_handling"))]
+pub fn into_bytes<T: Pod>(v: Vec<T>) -> Vec<u8> {
+    match try_cast_vec(v) {
+        Ok(bytes) => bytes,
+        Err((_, v)) => {
+            let bytes: &[u8] = bytemuck::cast_slice(&v);
+            let mut out = Vec::with_capacity(bytes.len());
+            unsafe {
+                let dst = out.spare_capacity_mut().as_mut_ptr() as *mut u8;
+                ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+                out.set_len(bytes.len());
+            }
+            out
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cast_same_alignment() {
+        let mut a = Vec::<[u16; 2]>::new();
+        for i in 0..4 {
+            a.push([i, i + 1]);
+        }
+        let cap = a.capacity();
+        let ptr = a.as_ptr() as usize;
+        let b: Vec<u16> = cast_vec(a);
+        assert_eq!(b.as_ptr() as usize, ptr);
+        assert_eq!(b.capacity(), cap * 2);
+        assert_eq!(&b[..4], &[0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn cast_errors() {
+        let mut a = Vec::<u8>::new();
+        a.push(1);
+        let (e, a) = try_cast_vec::<u8, u32>(a).unwrap_err();
+        assert_eq!(e, PodCastError::AlignmentMismatch);
+        let (e, _) = try_cast_vec::<u8, [u8; 2]>(a).unwrap_err();
+        assert_eq!(e, PodCastError::OutputSliceWouldHaveSlop);
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let mut bytes = Vec::new();
+        for b in [1u8, 0, 0, 0, 2, 0, 0, 0] {
+            bytes.push(b);
+        }
+        let words: Vec<u32> = from_bytes(bytes).unwrap();
+        assert_eq!(
+            &words[..],
+            &[
+                u32::from_le_bytes([1, 0, 0, 0]),
+                u32::from_le_bytes([2, 0, 0, 0])
+            ]
+        );
+        let bytes = into_bytes(words);
+        assert_eq!(&bytes[..], &[1, 0, 0, 0, 2, 0, 0, 0]);
+
+        let mut odd = Vec::new();
+        odd.push(1u8);
+        assert!(from_bytes::<u32>(odd).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::Vec;
+use core::cell::RefCell;
+use core::ops::{Deref, DerefMut};
+
+const CLASSES: usize = usize::BITS as usize;
+
+// Recycles buffers by power-of-two size class. A buffer whose capacity is at least `2^c` is
+// filed under class `c`, so any buffer taken from class `c` can hold a request rounded up to it.
+pub struct BufferPool<T> {
+    free: RefCell<[Vec<Vec<T>>; CLASSES]>,
+    max_per_class: usize,
+}
+
+impl<T> BufferPool<T> {
+    pub fn new(max_per_class: usize) -> Self {
+        Self {
+            free: RefCell::new(core::array::from_fn(|_| Vec::new())),
+            max_per_class,
+        }
+    }
+
+    pub fn with_capacity(&self, cap: usize) -> PooledVec<'_, T> {
+        let class = cap.next_power_of_two().trailing_zeros() as usize;
+        let vec = match self.free.borrow_mut()[class].pop() {
+            Some(vec) => vec,
+            None => Vec::with_capacity(1 << class),
+        };
+        PooledVec { vec, pool: self }
+    }
+
+    // Number of idle buffers held by the pool.
+    pub fn idle(&self) -> usize {
+        self.free.borrow().iter().map(|class| class.len()).sum()
+    }
+
+    fn put(&self, mut vec: Vec<T>) {
+        // A poisoned vector may be dropped while unwinding from the panic that poisoned it, so
+        // it is freed rather than cleared (which would panic again and abort).
+        if vec.capacity() == 0 || core::mem::size_of::<T>() == 0 || vec.poisoned {
+            return;
+        }
+        // The next user starts from a plain vector, and the policy must not shrink the buffer
+        // on the way in.
+        vec.buf.locked = false;
+        vec.buf.max_cap = usize::MAX;
+        vec.shrink = None;
+        vec.clear();
+        let class = (usize::BITS - 1 - vec.capacity().leading_zeros()) as usize;
+        let mut free = self.free.borrow_mut();
+        if free[class].len() < self.max_per_class {
+            free[class].push(vec);
+        }
+    }
+}
+
+// A vector whose buffer goes back to its pool when dropped.
+pub struct PooledVec<'a, T> {
+    vec: Vec<T>,
+    pool: &'a BufferPool<T>,
+}
+
+impl<T> PooledVec<'_, T> {
+    // Detaches the vector from the pool; its buffer will be freed normally.
+    pub fn into_inner(self) -> Vec<T> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        core::mem::take(&mut this.vec)
+    }
+}
+
+impl<T> Deref for PooledVec<'_, T> {
+    type Target = Vec<T>;
+    fn deref(&self) -> &Vec<T> {
+        &self.vec
+    }
+}
+
+impl<T> DerefMut for PooledVec<'_, T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.vec
+    }
+}
+
+impl<T> Drop for PooledVec<'_, T> {
+    fn drop(&mut self) {
+        self.pool.put(core::mem::take(&mut self.vec));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    #[test]
+    fn reuses_buffers() {
+        let pool = BufferPool::new(4);
+        let mut a = pool.with_capacity(100);
+        assert_eq!(a.capacity(), 128);
+        a.push(Box::new(1));
+        let ptr = a.as_ptr();
+        drop(a);
+        assert_eq!(pool.idle(), 1);
+
+        let b = pool.with_capacity(65);
+        assert_eq!(b.as_ptr(), ptr);
+        assert!(b.is_empty());
+        let c = pool.with_capacity(65);
+        assert_ne!(c.as_ptr(), ptr);
+        assert_eq!(pool.idle(), 0);
+
+        // Growing past the class files the buffer under the larger one.
+        let mut d = pool.with_capacity(1);
+        for i in 0..3 {
+            d.push(Box::new(i));
+        }
+        drop(d);
+        assert!(pool.with_capacity(3).capacity() >= 3);
+
+        drop((b, c));
+        let kept = pool.with_capacity(128).into_inner();
+        assert_eq!(kept.capacity(), 128);
+        assert_eq!(pool.idle(), 2);
+    }
+
+    #[test]
+    fn resets_settings() {
+        let pool = BufferPool::<u8>::new(4);
+        let mut a = pool.with_capacity(8);
+        a.push(1);
+        a.lock_capacity();
+        a.set_max_capacity(Some(8));
+        a.set_shrink_policy(Some(crate::ShrinkPolicy::below_fraction(4)));
+        drop(a);
+        let mut b = pool.with_capacity(8);
+        assert_eq!(b.capacity(), 8);
+        assert!(!b.is_capacity_locked());
+        assert_eq!(b.max_capacity(), None);
+        b.extend_from_slice(&[0; 9]);
+        b.clear();
+        assert_eq!(b.capacity(), 16);
+    }
+
+    #[test]
+    fn poisoned_vectors_are_not_pooled() {
+        struct PanicOnDrop;
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                panic!("drop");
+            }
+        }
+        let pool = BufferPool::new(4);
+        // The pooled vector is dropped while unwinding from the panic that poisoned it
+        let r = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut a = pool.with_capacity(4);
+            a.push(PanicOnDrop);
+            a.truncate(0);
+        }));
+        assert!(r.is_err());
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn bounded_per_class() {
+        let pool = BufferPool::<u8>::new(1);
+        let (a, b) = (pool.with_capacity(8), pool.with_capacity(8));
+        drop((a, b));
+        assert_eq!(pool.idle(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use core::alloc::{AllocError, Allocator, Layout};
+use core::cell::UnsafeCell;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+// Alignment of every block, and so the largest alignment the pool can serve.
+pub const POOL_ALIGN: usize = 16;
+
+#[repr(C, align(16))]
+struct Block<const SIZE: usize>([u8; SIZE]);
+
+// A fixed-block allocator over a byte region embedded in the value itself, meant to live in a
+// `static` on targets without a heap: `Vec::new_in(&POOL)`. Each allocation takes one whole
+// block, so requests larger than `BLOCK_SIZE` (or aligned above `POOL_ALIGN`) fail, and so does
+// any request once all `BLOCKS` are taken. Vectors see both as `TryReserveError::AllocError`
+// from the `try_*` methods. Growing within a block keeps the pointer and copies nothing.
+pub struct PoolAlloc<const BLOCK_SIZE: usize, const BLOCKS: usize> {
+    blocks: UnsafeCell<[Block<BLOCK_SIZE>; BLOCKS]>,
+    used: [AtomicBool; BLOCKS],
+}
+
+// Blocks are handed out exclusively through `used`.
+unsafe impl<const BLOCK_SIZE: usize, const BLOCKS: usize> Sync for PoolAlloc<BLOCK_SIZE, BLOCKS> {}
+
+impl<const BLOCK_SIZE: usize, const BLOCKS: usize> PoolAlloc<BLOCK_SIZE, BLOCKS> {
+    pub const fn new() -> Self {
+        Self {
+            blocks: UnsafeCell::new([const { Block([0; BLOCK_SIZE]) }; BLOCKS]),
+            used: [const { AtomicBool::new(false) }; BLOCKS],
+        }
+    }
+
+    pub fn free_blocks(&self) -> usize {
+        self.used
+            .iter()
+            .filter(|u| !u.load(Ordering::Relaxed))
+            .count()
+    }
+
+    fn fits(layout: Layout) -> bool {
+        layout.size() <= BLOCK_SIZE && layout.align() <= POOL_ALIGN
+    }
+
+    fn block_index(&self, ptr: NonNull<u8>) -> usize {
+        let base = self.blocks.get() as usize;
+        (ptr.as_ptr() as usize - base) / core::mem::size_of::<Block<BLOCK_SIZE>>()
+    }
+}
+
+impl<const BLOCK_SIZE: usize, const BLOCKS: usize> Default for PoolAlloc<BLOCK_SIZE, BLOCKS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<const BLOCK_SIZE: usize, const BLOCKS: usize> Allocator
+    for PoolAlloc<BLOCK_SIZE, BLOCKS>
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !Self::fits(layout) {
+            return Err(AllocError);
+        }
+        for (i, used) in self.used.iter().enumerate() {
+            if used
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                let block = unsafe { (self.blocks.get() as *mut Block<BLOCK_SIZE>).add(i) };
+                let ptr = NonNull::new(block as *mut u8).unwrap();
+                return Ok(NonNull::slice_from_raw_parts(ptr, BLOCK_SIZE));
+            }
+        }
+        Err(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        self.used[self.block_index(ptr)].store(false, Ordering::Release);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        _old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Every allocation already owns a whole block
+        if Self::fits(new_layout) {
+            Ok(NonNull::slice_from_raw_parts(ptr, BLOCK_SIZE))
+        } else {
+            Err(AllocError)
+        }
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Ok(NonNull::slice_from_raw_parts(ptr, BLOCK_SIZE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TryReserveError, Vec};
+
+    static POOL: PoolAlloc<64, 2> = PoolAlloc::new();
+
+    #[test]
+    fn vectors_in_a_static_pool() {
+        let mut a = Vec::new_in(&POOL);
+        for i in 0..16u32 {
+            a.try_push(i).unwrap();
+        }
+        let block = a.as_ptr();
+        assert!(matches!(
+            a.try_push(16),
+            Err((TryReserveError::AllocError { .. }, 16))
+        ));
+        assert_eq!(a.as_ptr(), block);
+        assert_eq!(a.iter().sum::<u32>(), 120);
+
+        let b = Vec::<u64, _>::try_with_capacity_in(8, &POOL).unwrap();
+        assert_eq!(POOL.free_blocks(), 0);
+        assert!(Vec::<u8, _>::try_with_capacity_in(1, &POOL).is_err());
+        drop(a);
+        assert_eq!(POOL.free_blocks(), 1);
+        assert!(Vec::<u128, _>::try_with_capacity_in(5, &POOL).is_err());
+        drop(b);
+        assert_eq!(POOL.free_blocks(), 2);
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    #[test]
+    fn full_api_on_pooled_vectors() {
+        static BYTES: PoolAlloc<64, 4> = PoolAlloc::new();
+        let mut a = Vec::new_in(&BYTES);
+        a.extend_from_slice(b"pooled");
+        a.extend(b" bytes".iter());
+        let mut b = Vec::new_in(&BYTES);
+        b.extend(*b"pooled bytes");
+        assert_eq!(a, b);
+        assert!(a <= b);
+
+        b.sort();
+        let rest = b.take(6..);
+        assert_eq!(rest, *b"oopsty");
+        assert_eq!(b, *b" bdeel");
+        assert!(b.into_iter().rev().eq(*b"leedb "));
+        drop((a, rest));
+        assert_eq!(BYTES.free_blocks(), 4);
+    }
+}
@@ -0,0 +1,142 @@
+// Kani harnesses for the unsafe core, run with `cargo kani`. Kani checks every pointer access,
+// free and arithmetic overflow on its own; the asserts below add the `len <= cap` invariant and
+// the element bookkeeping. Lengths are kept small so the loops unwind within the bounds given.
+use crate::{RawVec, Vec};
+use alloc::alloc::Layout;
+
+const MAX_LEN: usize = 4;
+
+fn any_vec(len: usize) -> Vec<u32> {
+    let mut v = Vec::new();
+    for _ in 0..len {
+        v.push(kani::any());
+    }
+    v
+}
+
+fn check_invariants<T>(v: &Vec<T>) {
+    assert!(v.len <= v.buf.cap);
+    assert!(core::mem::size_of::<T>() == 0 || Layout::array::<T>(v.buf.cap).is_ok());
+}
+
+#[kani::proof]
+#[kani::unwind(5)]
+fn raw_vec_grow() {
+    let mut buf = RawVec::<u16>::new();
+    let grows: usize = kani::any();
+    kani::assume(grows <= MAX_LEN);
+    for _ in 0..grows {
+        let old = buf.cap;
+        buf.grow();
+        assert!(buf.cap > old);
+        assert!(buf.cap >= 2 * old);
+        unsafe { buf.ptr.as_ptr().add(buf.cap - 1).write(kani::any()) };
+    }
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn push_pop() {
+    let len: usize = kani::any();
+    kani::assume(len <= MAX_LEN);
+    let mut v = Vec::new();
+    for i in 0..len {
+        v.push(i as u32);
+        check_invariants(&v);
+    }
+    assert_eq!(v.len(), len);
+    for i in (0..len).rev() {
+        assert_eq!(v.pop(), Some(i as u32));
+        check_invariants(&v);
+    }
+    assert_eq!(v.pop(), None);
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn insert_remove() {
+    let len: usize = kani::any();
+    kani::assume(len < MAX_LEN);
+    let mut v = any_vec(len);
+    let index: usize = kani::any();
+    kani::assume(index <= len);
+    let elem: u32 = kani::any();
+    let before = if index < len { Some(v[index]) } else { None };
+    v.insert(index, elem);
+    check_invariants(&v);
+    assert_eq!(v.len(), len + 1);
+    assert_eq!(v[index], elem);
+    if let Some(next) = before {
+        assert_eq!(v[index + 1], next);
+    }
+    assert_eq!(v.remove(index), elem);
+    check_invariants(&v);
+    assert_eq!(v.len(), len);
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn swap_remove_truncate() {
+    let len: usize = kani::any();
+    kani::assume(len >= 1 && len <= MAX_LEN);
+    let mut v = any_vec(len);
+    let index: usize = kani::any();
+    kani::assume(index < len);
+    let last = v[len - 1];
+    v.swap_remove(index);
+    check_invariants(&v);
+    if index < len - 1 {
+        assert_eq!(v[index], last);
+    }
+    let new_len: usize = kani::any();
+    v.truncate(new_len);
+    check_invariants(&v);
+    assert!(v.len() <= new_len);
+}
+
+// Partially consumed iterators must drop the rest exactly once and free the buffer.
+#[kani::proof]
+#[kani::unwind(6)]
+fn into_iter_partial() {
+    let len: usize = kani::any();
+    kani::assume(len <= MAX_LEN);
+    let mut v = Vec::new();
+    for i in 0..len {
+        v.push(alloc::boxed::Box::new(i));
+    }
+    let taken: usize = kani::any();
+    kani::assume(taken <= len);
+    let mut it = v.into_iter();
+    for i in 0..taken {
+        assert_eq!(it.next().map(|b| *b), Some(i));
+    }
+    if kani::any() {
+        assert_eq!(
+            it.next_back().map(|b| *b),
+            len.checked_sub(1).filter(|&l| l >= taken)
+        );
+    }
+}
+
+#[kani::proof]
+#[kani::unwind(6)]
+fn drain_partial() {
+    let len: usize = kani::any();
+    kani::assume(len <= MAX_LEN);
+    let mut v = Vec::new();
+    for i in 0..len {
+        v.push(alloc::boxed::Box::new(i));
+    }
+    let cap = v.capacity();
+    let taken: usize = kani::any();
+    kani::assume(taken <= len);
+    let mut d = v.drain();
+    for _ in 0..taken {
+        assert!(d.next().is_some());
+    }
+    drop(d);
+    assert_eq!(v.len(), 0);
+    assert_eq!(v.capacity(), cap);
+    v.push(alloc::boxed::Box::new(0));
+    check_invariants(&v);
+}
@@ -0,0 +1,273 @@
+//! Python classes over `Vec<i64>`, `Vec<f64>` and `Vec<u8>`.
+//!
+//! The classes behave like a typed `list` and export their buffer through the buffer protocol
+//! (`memoryview(v)`, `numpy.frombuffer(v, ...)`), so Python sees the vector's own memory. As
+//! with `bytearray`, operations that could reallocate raise `BufferError` while a buffer is
+//! exported. Add the classes to a module with [`register`]; the `rust_vec` module below does
+//! that when the crate is built as a `cdylib`.
+use crate::Vec;
+use alloc::boxed::Box;
+use core::ffi::{c_char, c_int, c_void};
+use pyo3::exceptions::{PyBufferError, PyIndexError, PyMemoryError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::string::String;
+
+fn normalize(index: isize, len: usize) -> PyResult<usize> {
+    let i = if index < 0 {
+        index + len as isize
+    } else {
+        index
+    };
+    if i < 0 || i as usize >= len {
+        Err(PyIndexError::new_err("index out of range"))
+    } else {
+        Ok(i as usize)
+    }
+}
+
+macro_rules! py_vec {
+    ($name:ident, $py_name:literal, $elem:ty, $format:literal) => {
+        #[pyclass(name = $py_name)]
+        pub struct $name {
+            vec: Vec<$elem>,
+            exports: usize,
+        }
+
+        impl $name {
+            fn check_resizable(&self) -> PyResult<()> {
+                if self.exports != 0 {
+                    return Err(PyBufferError::new_err(
+                        "cannot resize a vector while a buffer is exported",
+                    ));
+                }
+                Ok(())
+            }
+        }
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            #[pyo3(signature = (items = None))]
+            fn new(items: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+                let mut v = Self {
+                    vec: Vec::new(),
+                    exports: 0,
+                };
+                if let Some(items) = items {
+                    v.extend(items)?;
+                }
+                Ok(v)
+            }
+
+            fn __len__(&self) -> usize {
+                self.vec.len()
+            }
+
+            fn __getitem__(&self, index: isize) -> PyResult<$elem> {
+                Ok(self.vec[normalize(index, self.vec.len())?])
+            }
+
+            fn __setitem__(&mut self, index: isize, value: $elem) -> PyResult<()> {
+                let i = normalize(index, self.vec.len())?;
+                self.vec[i] = value;
+                Ok(())
+            }
+
+            fn __repr__(&self) -> String {
+                std::format!("{}({:?})", $py_name, &self.vec[..])
+            }
+
+            fn append(&mut self, value: $elem) -> PyResult<()> {
+                self.check_resizable()?;
+                self.vec.push(value);
+                Ok(())
+            }
+
+            fn extend(&mut self, items: &Bound<'_, PyAny>) -> PyResult<()> {
+                self.check_resizable()?;
+                for item in items.try_iter()? {
+                    self.vec.push(item?.extract()?);
+                }
+                Ok(())
+            }
+
+            // Out-of-range indices clamp to the ends, as with `list.insert`.
+            fn insert(&mut self, index: isize, value: $elem) -> PyResult<()> {
+                self.check_resizable()?;
+                let len = self.vec.len() as isize;
+                let i = if index < 0 { index + len } else { index };
+                self.vec.insert(i.clamp(0, len) as usize, value);
+                Ok(())
+            }
+
+            #[pyo3(signature = (index = -1))]
+            fn pop(&mut self, index: isize) -> PyResult<$elem> {
+                self.check_resizable()?;
+                if self.vec.is_empty() {
+                    return Err(PyIndexError::new_err("pop from empty vector"));
+                }
+                Ok(self.vec.remove(normalize(index, self.vec.len())?))
+            }
+
+            fn clear(&mut self) -> PyResult<()> {
+                self.check_resizable()?;
+                self.vec.clear();
+                Ok(())
+            }
+
+            fn capacity(&self) -> usize {
+                self.vec.capacity()
+            }
+
+            fn reserve(&mut self, additional: usize) -> PyResult<()> {
+                self.check_resizable()?;
+                self.vec
+                    .try_reserve(additional)
+                    .map_err(|e| PyMemoryError::new_err(std::format!("{}", e)))
+            }
+
+            fn tolist(&self) -> std::vec::Vec<$elem> {
+                self.vec.iter().copied().collect()
+            }
+
+            unsafe fn __getbuffer__(
+                slf: Bound<'_, Self>,
+                view: *mut ffi::Py_buffer,
+                flags: c_int,
+            ) -> PyResult<()> {
+                if view.is_null() {
+                    return Err(PyBufferError::new_err("view is null"));
+                }
+                let mut this = slf.borrow_mut();
+                this.exports += 1;
+                unsafe {
+                    (*view).buf = this.vec.as_mut_ptr() as *mut c_void;
+                    (*view).len = (this.vec.len() * core::mem::size_of::<$elem>()) as isize;
+                    (*view).readonly = 0;
+                    (*view).itemsize = core::mem::size_of::<$elem>() as isize;
+                    (*view).format = if flags & ffi::PyBUF_FORMAT != 0 {
+                        concat!($format, "\0").as_ptr() as *mut c_char
+                    } else {
+                        core::ptr::null_mut()
+                    };
+                    (*view).ndim = 1;
+                    // The element count has to outlive this call; it is freed on release
+                    (*view).internal = core::ptr::null_mut();
+                    (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND {
+                        let shape = Box::into_raw(Box::new(this.vec.len() as isize));
+                        (*view).internal = shape as *mut c_void;
+                        shape
+                    } else {
+                        core::ptr::null_mut()
+                    };
+                    (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES {
+                        &mut (*view).itemsize
+                    } else {
+                        core::ptr::null_mut()
+                    };
+                    (*view).suboffsets = core::ptr::null_mut();
+                    drop(this);
+                    (*view).obj = slf.into_any().into_ptr();
+                }
+                Ok(())
+            }
+
+            unsafe fn __releasebuffer__(&mut self, view: *mut ffi::Py_buffer) {
+                self.exports -= 1;
+                unsafe {
+                    let shape = (*view).internal as *mut isize;
+                    if !shape.is_null() {
+                        drop(Box::from_raw(shape));
+                    }
+                }
+            }
+        }
+    };
+}
+
+py_vec!(PyVecI64, "VecI64", i64, "q");
+py_vec!(PyVecF64, "VecF64", f64, "d");
+py_vec!(PyVecU8, "VecU8", u8, "B");
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVecI64>()?;
+    m.add_class::<PyVecF64>()?;
+    m.add_class::<PyVecU8>()?;
+    Ok(())
+}
+
+#[pymodule]
+fn rust_vec(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    register(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::py_run;
+
+    fn with_py(f: impl FnOnce(Python<'_>, &Bound<'_, PyModule>)) {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = PyModule::new(py, "rust_vec").unwrap();
+            register(&m).unwrap();
+            f(py, &m)
+        });
+    }
+
+    #[test]
+    fn list_methods() {
+        with_py(|py, m| {
+            py_run!(
+                py,
+                m,
+                r#"
+v = m.VecI64([1, 2, 3])
+v.append(4)
+v.insert(0, 0)
+v.insert(-1, 9)
+assert v.pop() == 4
+assert v.pop(0) == 0
+assert v.pop() == 9
+v[-1] = 5
+assert list(v) == [1, 2, 5]
+assert len(v) == 3 and v[-3] == 1
+try:
+    v[3]
+    assert False
+except IndexError:
+    pass
+v.extend(range(2))
+assert v.tolist() == [1, 2, 5, 0, 1]
+assert repr(m.VecF64([0.5])) == "VecF64([0.5])"
+"#
+            );
+        });
+    }
+
+    #[test]
+    fn buffer_protocol() {
+        with_py(|py, m| {
+            py_run!(
+                py,
+                m,
+                r#"
+v = m.VecF64([1.0, 2.5])
+view = memoryview(v)
+assert view.format == "d" and view.shape == (2,) and view.tolist() == [1.0, 2.5]
+view[0] = 4.0
+assert v[0] == 4.0
+try:
+    v.append(1.0)
+    assert False
+except BufferError:
+    pass
+view.release()
+v.append(1.0)
+assert bytes(m.VecU8(b"ab")) == b"ab"
+"#
+            );
+        });
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{RawVec, Vec};
+use core::alloc::Allocator;
+use core::{mem, ptr};
+
+// Integer keys for `sort_radix`. `radix_key` maps a value to a u64 whose unsigned order matches
+// the value's order, so signed types flip their sign bit; only the low `BYTES` bytes are sorted.
+pub trait RadixKey: Copy {
+    const BYTES: usize;
+    fn radix_key(self) -> u64;
+}
+
+macro_rules! radix_key {
+    ($($unsigned:ty),* ; $($signed:ty => $as:ty),*) => {
+        $(impl RadixKey for $unsigned {
+            const BYTES: usize = mem::size_of::<$unsigned>();
+            fn radix_key(self) -> u64 {
+                self as u64
+            }
+        })*
+        $(impl RadixKey for $signed {
+            const BYTES: usize = mem::size_of::<$signed>();
+            fn radix_key(self) -> u64 {
+                (self as $as ^ <$signed>::MIN as $as) as u64
+            }
+        })*
+    };
+}
+
+radix_key!(u8, u16, u32, u64, usize ; i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+impl<T: RadixKey, A: Allocator> Vec<T, A> {
+    // Stable LSD radix sort, one pass per key byte. Passes where every element lands in the same
+    // bucket are skipped, so small values in wide types only pay for the bytes they use.
+    pub fn sort_radix(&mut self) {
+        lsd_sort(self, T::BYTES, |x| x.radix_key());
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    // Stable radix sort by an integer key. Keys are computed once up front, so `f` is called
+    // exactly `len` times; the sorted order is then applied as a permutation.
+    pub fn sort_radix_by_key<K: RadixKey, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        let mut keyed: Vec<(u64, usize)> = Vec::with_capacity(self.len());
+        for (i, x) in self.iter().enumerate() {
+            keyed.push((f(x).radix_key(), i));
+        }
+        lsd_sort(&mut keyed, K::BYTES, |&(k, _)| k);
+        let perm: Vec<usize> = keyed.iter().map(|&(_, i)| i).collect();
+        self.permute_in_place(&perm);
+    }
+}
+
+// Ping-pongs the elements between `v` and a scratch buffer of the same length. Elements are
+// moved bitwise and `key` is only ever our own pure extractor, so nothing can panic while
+// values are in flight; the scratch buffer keeps length 0 and never drops anything.
+fn lsd_sort<T, A: Allocator, F: Fn(&T) -> u64>(v: &mut Vec<T, A>, bytes: usize, key: F) {
+    let n = v.len();
+    if n < 2 {
+        return;
+    }
+    let base = v.as_mut_ptr();
+    let scratch = RawVec::<T, &A>::with_capacity_in(n, &v.buf.alloc);
+    let (mut src, mut dst) = (base, scratch.ptr.as_ptr());
+    for pass in 0..bytes {
+        let shift = pass * 8;
+        let mut counts = [0usize; 256];
+        for i in 0..n {
+            counts[(key(unsafe { &*src.add(i) }) >> shift) as usize & 0xff] += 1;
+        }
+        if counts.contains(&n) {
+            continue;
+        }
+        let mut offsets = [0usize; 256];
+        let mut sum = 0;
+        for (o, c) in offsets.iter_mut().zip(counts.iter()) {
+            *o = sum;
+            sum += c;
+        }
+        for i in 0..n {
+            unsafe {
+                let b = (key(&*src.add(i)) >> shift) as usize & 0xff;
+                ptr::copy_nonoverlapping(src.add(i), dst.add(offsets[b]), 1);
+                offsets[b] += 1;
+            }
+        }
+        mem::swap(&mut src, &mut dst);
+    }
+    if src != base {
+        unsafe { ptr::copy_nonoverlapping(src, base, n) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    fn lcg(seed: &mut u64) -> u64 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *seed >> 11
+    }
+
+    #[test]
+    fn sorts_like_std() {
+        let mut seed = 1;
+        let raw: std::vec::Vec<u64> = (0..3000).map(|_| lcg(&mut seed)).collect();
+
+        let mut a: Vec<u32> = raw.iter().map(|&x| x as u32).collect();
+        let mut want: std::vec::Vec<u32> = a.iter().copied().collect();
+        a.sort_radix();
+        want.sort();
+        assert_eq!(&a[..], &want[..]);
+
+        let mut a: Vec<i64> = raw.iter().map(|&x| (x as i64).wrapping_mul(3)).collect();
+        let mut want: std::vec::Vec<i64> = a.iter().copied().collect();
+        a.sort_radix();
+        want.sort();
+        assert_eq!(&a[..], &want[..]);
+
+        let mut a: Vec<i8> = raw.iter().map(|&x| x as i8).collect();
+        let mut want: std::vec::Vec<i8> = a.iter().copied().collect();
+        a.sort_radix();
+        want.sort();
+        assert_eq!(&a[..], &want[..]);
+    }
+
+    #[test]
+    fn by_key_is_stable() {
+        let mut seed = 2;
+        let mut a: Vec<(i16, Box<usize>)> = (0..1000)
+            .map(|i| ((lcg(&mut seed) % 50) as i16 - 25, Box::new(i)))
+            .collect();
+        let mut want: std::vec::Vec<(i16, usize)> = a.iter().map(|(k, b)| (*k, **b)).collect();
+        a.sort_radix_by_key(|x| x.0);
+        want.sort_by_key(|x| x.0);
+        let got: std::vec::Vec<(i16, usize)> = a.iter().map(|(k, b)| (*k, **b)).collect();
+        assert_eq!(got, want);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::Rng;
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        (**self).shuffle(rng)
+    }
+
+    // Moves `amount` randomly chosen elements to the front, shuffled, and returns them along
+    // with the rest.
+    pub fn partial_shuffle<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        amount: usize,
+    ) -> (&mut [T], &mut [T]) {
+        (**self).partial_shuffle(rng, amount)
+    }
+
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        (**self).choose(rng)
+    }
+
+    // `min(k, len)` distinct elements in random order.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<&T> {
+        let mut out = Vec::with_capacity(k.min(self.len()));
+        out.extend((**self).sample(rng, k));
+        out
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    fn range(n: u32) -> Vec<u32> {
+        let mut v = Vec::new();
+        v.extend(0..n);
+        v
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut v = range(100);
+        v.shuffle(&mut rng);
+        assert_ne!(v, range(100));
+        v.sort();
+        assert_eq!(v, range(100));
+
+        let (chosen, rest) = v.partial_shuffle(&mut rng, 10);
+        assert_eq!((chosen.len(), rest.len()), (10, 90));
+    }
+
+    #[test]
+    fn choose_and_sample() {
+        let mut rng = SmallRng::seed_from_u64(2);
+        let v = range(10);
+        assert!(*v.choose(&mut rng).unwrap() < 10);
+        assert_eq!(Vec::<u32>::new().choose(&mut rng), None);
+
+        let mut s: std::vec::Vec<u32> = v.sample(&mut rng, 5).iter().map(|&&x| x).collect();
+        s.sort();
+        s.dedup();
+        assert_eq!(s.len(), 5);
+        assert_eq!(v.sample(&mut rng, 20).len(), 10);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{MemoryReport, RawVec};
+use core::{mem, ptr};
+
+// Keeps the last `capacity` elements pushed. The buffer is allocated once; when it is full a
+// push overwrites the oldest element, which moves `head` forward. `capacity` is kept separately
+// from the buffer because `RawVec` reports an unbounded capacity for zero-sized types.
+pub struct RingBuffer<T> {
+    buf: RawVec<T>,
+    capacity: usize,
+    head: usize,
+    len: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buf: RawVec::with_capacity(capacity),
+            capacity,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn slot(&self, i: usize) -> *mut T {
+        let i = (self.head + i) % self.capacity;
+        unsafe { self.buf.ptr.as_ptr().add(i) }
+    }
+
+    // Appends `elem` as the newest element, returning the oldest one if it had to make room.
+    // With a capacity of zero nothing is kept and `elem` comes straight back.
+    pub fn push(&mut self, elem: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(elem);
+        }
+        if self.is_full() {
+            let slot = self.slot(0);
+            let old = unsafe { ptr::replace(slot, elem) };
+            self.head = (self.head + 1) % self.capacity;
+            Some(old)
+        } else {
+            unsafe { ptr::write(self.slot(self.len), elem) };
+            self.len += 1;
+            None
+        }
+    }
+
+    // Removes the oldest element.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let elem = unsafe { ptr::read(self.slot(0)) };
+        self.head = (self.head + 1) % self.capacity;
+        self.len -= 1;
+        Some(elem)
+    }
+
+    // Index 0 is the oldest element.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { &*self.slot(index) })
+        } else {
+            None
+        }
+    }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    // The contents from oldest to newest, as the part up to the end of the buffer and the part
+    // that wrapped around to its start.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        if self.len == 0 {
+            return (&[], &[]);
+        }
+        let first = self.len.min(self.capacity - self.head);
+        unsafe {
+            let p = self.buf.ptr.as_ptr();
+            (
+                core::slice::from_raw_parts(p.add(self.head), first),
+                core::slice::from_raw_parts(p, self.len - first),
+            )
+        }
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> MemoryReport for RingBuffer<T> {
+    fn allocated_bytes(&self) -> usize {
+        self.capacity * mem::size_of::<T>()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.len * mem::size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+
+    fn contents(r: &RingBuffer<Box<usize>>) -> std::vec::Vec<usize> {
+        r.iter().map(|x| **x).collect()
+    }
+
+    #[test]
+    fn keeps_last_n() {
+        let mut r = RingBuffer::new(3);
+        assert!(r.push(Box::new(0)).is_none());
+        assert!(r.push(Box::new(1)).is_none());
+        assert_eq!(contents(&r), [0, 1]);
+        assert!(r.push(Box::new(2)).is_none());
+        assert_eq!(r.push(Box::new(3)).map(|x| *x), Some(0));
+        assert_eq!(r.push(Box::new(4)).map(|x| *x), Some(1));
+        assert_eq!(contents(&r), [2, 3, 4]);
+        assert_eq!(r.as_slices().1.len(), 2);
+        assert_eq!(r.get(0).map(|x| **x), Some(2));
+        assert_eq!(r.newest().map(|x| **x), Some(4));
+        assert_eq!(r.pop_front().map(|x| *x), Some(2));
+        r.push(Box::new(5));
+        assert_eq!(contents(&r), [3, 4, 5]);
+        assert_eq!(r.allocated_bytes(), 3 * mem::size_of::<Box<i32>>());
+        assert_eq!(r.spare_capacity_bytes(), 0);
+        assert!(r.iter().rev().map(|x| **x).eq([5, 4, 3]));
+    }
+
+    #[test]
+    fn degenerate_capacities() {
+        let mut r = RingBuffer::new(0);
+        assert_eq!(r.push(1), Some(1));
+        assert!(r.is_empty() && r.iter().next().is_none());
+
+        let mut z = RingBuffer::new(2);
+        for _ in 0..5 {
+            z.push(());
+        }
+        assert_eq!(z.len(), 2);
+        assert_eq!(z.iter().count(), 2);
+        assert_eq!(z.allocated_bytes(), 0);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{MemoryReport, Vec};
+use alloc::rc::Rc;
+use core::ops::Index;
+
+const SEGMENT_LEN: usize = 64;
+
+// A vector stored as fixed-size, reference-counted segments. A snapshot only bumps the segment
+// counts; a segment is copied the first time it is written after being shared, so rolling back
+// costs as much as the edits made since the snapshot rather than the whole buffer.
+pub struct SegmentedVec<T> {
+    segments: Vec<Rc<Vec<T>>>,
+    len: usize,
+}
+
+pub struct Snapshot<T> {
+    segments: Vec<Rc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T: Clone> SegmentedVec<T> {
+    pub fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.segments[index / SEGMENT_LEN][index % SEGMENT_LEN])
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&mut Self::unshare(&mut self.segments[index / SEGMENT_LEN])[index % SEGMENT_LEN])
+    }
+
+    pub fn push(&mut self, elem: T) {
+        if self.len.is_multiple_of(SEGMENT_LEN) {
+            self.segments.push(Rc::new(Vec::with_capacity(SEGMENT_LEN)));
+        }
+        let last = self.segments.len() - 1;
+        Self::unshare(&mut self.segments[last]).push(elem);
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let last = self.segments.len() - 1;
+        let elem = Self::unshare(&mut self.segments[last]).pop();
+        if self.segments[last].is_empty() {
+            self.segments.pop();
+        }
+        self.len -= 1;
+        elem
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.segments.iter().flat_map(|s| s.iter())
+    }
+
+    pub fn snapshot(&self) -> Snapshot<T> {
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for s in self.segments.iter() {
+            segments.push(Rc::clone(s));
+        }
+        Snapshot {
+            segments,
+            len: self.len,
+        }
+    }
+
+    // Restores the contents captured by `snapshot`. Segments not written since are reused as is.
+    pub fn rollback(&mut self, snapshot: Snapshot<T>) {
+        self.segments = snapshot.segments;
+        self.len = snapshot.len;
+    }
+
+    fn unshare(segment: &mut Rc<Vec<T>>) -> &mut Vec<T> {
+        if Rc::get_mut(segment).is_none() {
+            let mut copy = Vec::with_capacity(SEGMENT_LEN);
+            copy.extend(segment.iter().cloned());
+            *segment = Rc::new(copy);
+        }
+        Rc::get_mut(segment).unwrap()
+    }
+}
+
+impl<T: Clone> Default for SegmentedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Index<usize> for SegmentedVec<T> {
+    type Output = T;
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T> MemoryReport for SegmentedVec<T> {
+    // Segments still shared with a snapshot are counted here as well as there.
+    fn allocated_bytes(&self) -> usize {
+        let segments: usize = self.segments.iter().map(|s| s.allocated_bytes()).sum();
+        segments + self.segments.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        let segments: usize = self.segments.iter().map(|s| s.used_bytes()).sum();
+        segments + self.segments.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollback_restores_contents() {
+        let mut v = SegmentedVec::new();
+        for i in 0..200 {
+            v.push(i);
+        }
+        let snap = v.snapshot();
+        *v.get_mut(5).unwrap() = 500;
+        *v.get_mut(150).unwrap() = 1500;
+        for _ in 0..100 {
+            v.pop();
+        }
+        v.push(-1);
+        assert_eq!(v.len(), 101);
+        assert_eq!(v[5], 500);
+
+        v.rollback(snap);
+        assert_eq!(v.len(), 200);
+        assert!(v.iter().copied().eq(0..200));
+    }
+
+    #[test]
+    fn untouched_segments_are_shared() {
+        let mut v = SegmentedVec::new();
+        for i in 0..3 * SEGMENT_LEN {
+            v.push(i);
+        }
+        let snap = v.snapshot();
+        *v.get_mut(0).unwrap() = 7;
+        assert!(!Rc::ptr_eq(&v.segments[0], &snap.segments[0]));
+        assert!(Rc::ptr_eq(&v.segments[1], &snap.segments[1]));
+        assert!(Rc::ptr_eq(&v.segments[2], &snap.segments[2]));
+        assert!(v.get(3 * SEGMENT_LEN).is_none());
+    }
+}
@@ -0,0 +1,101 @@
+//! Serializes a `Vec<u8>` as a single bytes value instead of a sequence of integers:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Chunk {
+//!     #[serde(with = "rust_vec::serde_bytes")]
+//!     data: rust_vec::Vec<u8>,
+//! }
+//! ```
+//!
+//! Binary formats (CBOR, bincode, MessagePack) then write the buffer in one piece. Formats
+//! without a bytes type, like JSON, still see a sequence, and deserializing accepts either.
+use crate::serde_vec::cautious_capacity;
+use crate::Vec;
+use core::fmt;
+use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::Serializer;
+
+pub fn serialize<S: Serializer>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(bytes)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte buffer")
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let mut out = Vec::with_capacity(v.len());
+        out.extend_from_slice(v);
+        Ok(out)
+    }
+
+    // Takes over the deserializer's buffer without copying.
+    fn visit_byte_buf<E: Error>(self, v: alloc::vec::Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = Vec::with_capacity(cautious_capacity::<u8>(seq.size_hint()));
+        while let Some(b) = seq.next_element()? {
+            out.push(b);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde_test::{assert_de_tokens, assert_tokens, Token};
+
+    #[derive(Debug, PartialEq)]
+    struct Blob(Vec<u8>);
+
+    impl Serialize for Blob {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            super::serialize(&self.0, serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Blob {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            super::deserialize(deserializer).map(Blob)
+        }
+    }
+
+    fn blob(bytes: &[u8]) -> Blob {
+        let mut v = Vec::new();
+        v.extend_from_slice(bytes);
+        Blob(v)
+    }
+
+    #[test]
+    fn bytes_value() {
+        assert_tokens(&blob(b"abc"), &[Token::Bytes(b"abc")]);
+        assert_de_tokens(&blob(b"abc"), &[Token::ByteBuf(b"abc")]);
+        assert_de_tokens(
+            &blob(&[1, 2]),
+            &[
+                Token::Seq { len: Some(2) },
+                Token::U8(1),
+                Token::U8(2),
+                Token::SeqEnd,
+            ],
+        );
+    }
+}
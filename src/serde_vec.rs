@@ -0,0 +1,69 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+// Any vector is a sequence. For byte buffers that is one value per byte, which is slow and
+// bloats most formats; annotate such fields with `#[serde(with = "rust_vec::serde_bytes")]`.
+impl<T: Serialize, A: Allocator> Serialize for Vec<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// Upper bound on the capacity reserved from a size hint, so that a forged length can't force
+// a huge allocation before any element has arrived.
+const PREALLOC_BYTES: usize = 1 << 20;
+
+pub(crate) fn cautious_capacity<T>(hint: Option<usize>) -> usize {
+    let max = PREALLOC_BYTES / mem::size_of::<T>().max(1);
+    hint.unwrap_or(0).min(max)
+}
+
+struct SeqVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SeqVisitor<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut v = Vec::with_capacity(cautious_capacity::<T>(seq.size_hint()));
+        while let Some(elem) = seq.next_element()? {
+            v.push(elem);
+        }
+        Ok(v)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vec<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+    use serde_test::{assert_tokens, Token};
+
+    #[test]
+    fn sequence() {
+        let mut v = Vec::new();
+        v.extend_from_slice(&[1u16, 2]);
+        assert_tokens(
+            &v,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::U16(1),
+                Token::U16(2),
+                Token::SeqEnd,
+            ],
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::slice;
+
+// A std copy of the vector that every instrumented mutation is replayed on, so that a bug in
+// this crate's unsafe code shows up as a mismatch right after the call that caused it. The
+// `Clone` and `PartialEq` impls are captured as function pointers when shadowing is enabled,
+// which keeps `Vec<T>` itself free of those bounds.
+//
+// The pointers are typed, which makes `Vec<T>` invariant in `T` while the feature is on. That is
+// deliberate: a pointer captured for `Foo<'static>` must never run on the `Foo<'a>` a covariant
+// vector could have been coerced to, since a `Clone` impl that only exists for `'static` could
+// then extend a borrow.
+//
+// Mutations that can't be replayed (through `DerefMut`, `set_len` or a cursor) mark the copy
+// stale; it is then rebuilt from the vector after the next instrumented call instead of checked.
+pub(crate) struct Shadow<T> {
+    vec: alloc::vec::Vec<T>,
+    clone: fn(&T) -> T,
+    eq: fn(&[T], &[T]) -> bool,
+    stale: bool,
+}
+
+impl<T> Shadow<T> {
+    pub(crate) fn edit(&mut self, f: impl FnOnce(&mut alloc::vec::Vec<T>, fn(&T) -> T)) {
+        if !self.stale {
+            f(&mut self.vec, self.clone);
+        }
+    }
+
+    pub(crate) fn push(&mut self, elem: &T) {
+        self.edit(|v, clone| v.push(clone(elem)));
+    }
+
+    pub(crate) fn insert(&mut self, index: usize, elem: &T) {
+        self.edit(|v, clone| v.insert(index, clone(elem)));
+    }
+
+    pub(crate) fn extend(&mut self, elems: &[T]) {
+        self.edit(|v, clone| v.extend(elems.iter().map(clone)));
+    }
+
+    // Replays a removal and checks that both sides removed the same element.
+    pub(crate) fn take(&mut self, ours: &T, f: impl FnOnce(&mut alloc::vec::Vec<T>) -> T) {
+        if !self.stale {
+            let theirs = f(&mut self.vec);
+            assert!(
+                (self.eq)(slice::from_ref(ours), slice::from_ref(&theirs)),
+                "shadow check failed: removed a different element"
+            );
+        }
+    }
+
+    fn check(&mut self, op: &str, ours: &[T]) {
+        if self.stale {
+            self.vec = ours.iter().map(self.clone).collect();
+            self.stale = false;
+        } else {
+            assert!(
+                (self.eq)(ours, &self.vec),
+                "shadow check failed after `{}`",
+                op
+            );
+        }
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    // Starts checking this vector against a std copy after every mutation. Debugging aid for
+    // the `shadow` feature; every operation also pays for a clone and a full comparison.
+    pub fn enable_shadow(&mut self)
+    where
+        T: Clone + PartialEq,
+    {
+        self.shadow = Some(Shadow {
+            vec: alloc::vec::Vec::new(),
+            clone: T::clone,
+            eq: <[T]>::eq,
+            stale: true,
+        });
+        self.shadow_replay("enable_shadow", |_, _| {});
+    }
+
+    pub fn disable_shadow(&mut self) {
+        self.shadow = None;
+    }
+
+    pub(crate) fn shadow_replay(&mut self, op: &str, f: impl FnOnce(&mut Shadow<T>, &[T])) {
+        if let Some(shadow) = self.shadow.as_mut() {
+            // Not through `Deref`, which would panic on a poisoned vector
+            let ours = unsafe { slice::from_raw_parts(self.buf.ptr.as_ptr(), self.len) };
+            f(shadow, ours);
+            shadow.check(op, ours);
+        }
+    }
+
+    pub(crate) fn shadow_stale(&mut self) {
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow.stale = true;
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use core::ptr;
+
+    #[test]
+    fn follows_mutations() {
+        let mut v = Vec::new();
+        v.enable_shadow();
+        for i in 0..20 {
+            v.push(i.to_string());
+        }
+        v.insert(3, String::from("x"));
+        v.remove(0);
+        v.swap_remove(5);
+        v.pop();
+        v.extend(["a", "b"].iter().map(|s| s.to_string()));
+        drop(v.take(2..4));
+        v[0].push('!');
+        v.truncate(10);
+        v.push(String::new());
+        v.drain().count();
+        v.push(String::from("end"));
+        assert_eq!(v.shadow.as_ref().unwrap().vec, ["end"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "shadow check failed after `push`")]
+    fn catches_divergence() {
+        let mut v = Vec::new();
+        v.enable_shadow();
+        v.push(1);
+        // Corrupt the buffer without going through DerefMut
+        unsafe { ptr::write(v.buf.ptr.as_ptr(), 2) };
+        v.push(3);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::Vec;
+use core::alloc::Allocator;
+use core::simd::cmp::SimdPartialEq;
+use core::simd::Simd;
+
+// Primitive element types whose linear search is done 32 bytes at a time. Floats compare with
+// `==`, so NaN is never found and `-0.0` matches `0.0`, as with `slice::contains`.
+pub trait SimdFind: Copy + PartialEq {
+    fn find_in(haystack: &[Self], needle: Self) -> Option<usize>;
+}
+
+macro_rules! simd_find {
+    ($($t:ty),*) => {$(
+        impl SimdFind for $t {
+            fn find_in(haystack: &[$t], needle: $t) -> Option<usize> {
+                const LANES: usize = 32 / core::mem::size_of::<$t>();
+                let splat = Simd::<$t, LANES>::splat(needle);
+                let chunks = haystack.chunks_exact(LANES);
+                let rest = chunks.remainder();
+                for (i, chunk) in chunks.enumerate() {
+                    let mask = Simd::from_slice(chunk).simd_eq(splat);
+                    if let Some(j) = mask.first_set() {
+                        return Some(i * LANES + j);
+                    }
+                }
+                let base = haystack.len() - rest.len();
+                rest.iter().position(|&x| x == needle).map(|j| base + j)
+            }
+        }
+    )*};
+}
+
+simd_find!(u8, u16, u32, u64, f32);
+
+impl<T: SimdFind, A: Allocator> Vec<T, A> {
+    pub fn find_index(&self, value: &T) -> Option<usize> {
+        T::find_in(self, *value)
+    }
+
+    // Shadows `<[T]>::contains` for the types above.
+    pub fn contains(&self, value: &T) -> bool {
+        self.find_index(value).is_some()
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_first_match() {
+        let mut v = Vec::new();
+        v.extend((0..100u32).map(|i| i % 37));
+        assert_eq!(v.find_index(&0), Some(0));
+        assert_eq!(v.find_index(&36), Some(36));
+        assert_eq!(v.find_index(&99), None);
+        assert!(v.contains(&24));
+        // 100 is past the last full chunk of 8 lanes.
+        v.push(100);
+        assert_eq!(v.find_index(&100), Some(100));
+
+        let mut bytes = Vec::new();
+        bytes.extend(core::iter::repeat_n(0u8, 70));
+        bytes.push(7);
+        assert_eq!(bytes.find_index(&7), Some(70));
+    }
+
+    #[test]
+    fn float_equality() {
+        let mut v = Vec::new();
+        v.extend([1.0f32, f32::NAN, -0.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(v.find_index(&f32::NAN), None);
+        assert_eq!(v.find_index(&0.0), Some(2));
+        assert_eq!(v.find_index(&8.0), Some(9));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{RawVec, Vec};
+use core::alloc::Allocator;
+use core::cmp::Ordering;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+// Runs this short are insertion sorted instead of split further.
+const INSERTION_CUTOFF: usize = 20;
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(|a, b| a.cmp(b));
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    // Stable top-down merge sort. The scratch buffer holds the left half of a merge, so it is
+    // allocated once with `len / 2` slots. If `cmp` panics every element is still in `self`
+    // exactly once, just not in sorted order.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut cmp: F) {
+        self.check_poison();
+        shadow!(self, stale);
+        let len = self.len;
+        let v = self.buf.ptr.as_ptr();
+        let mut is_less = |a: &T, b: &T| cmp(a, b) == Ordering::Less;
+        if len <= INSERTION_CUTOFF {
+            unsafe { insertion_sort(v, len, &mut is_less) };
+            return;
+        }
+        let scratch = RawVec::<T, &A>::with_capacity_in(len / 2, &self.buf.alloc);
+        unsafe { merge_sort(v, len, scratch.ptr.as_ptr(), &mut is_less) };
+    }
+
+    // The permutation that sorts `self` stably: `self.gather(&perm)` is sorted, and
+    // `permute_in_place(&perm)` sorts in place. Other vectors of the same length can be reordered
+    // with the same permutation.
+    pub fn argsort(&self) -> Vec<usize>
+    where
+        T: Ord,
+    {
+        self.argsort_by(|a, b| a.cmp(b))
+    }
+
+    pub fn argsort_by_key<K: Ord, F: FnMut(&T) -> K>(&self, mut f: F) -> Vec<usize> {
+        self.argsort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    pub fn argsort_by<F: FnMut(&T, &T) -> Ordering>(&self, mut cmp: F) -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..self.len).collect();
+        perm.sort_by(|&i, &j| cmp(&self[i], &self[j]));
+        perm
+    }
+
+    // The `k` largest elements, largest first. Selection partitions references to the elements
+    // around the k-th largest in linear time and only the selected `k` get sorted, so this is
+    // O(n + k log k). Ties are broken arbitrarily.
+    pub fn top_k(&self, k: usize) -> Vec<&T>
+    where
+        T: Ord,
+    {
+        self.top_k_by(k, |a, b| a.cmp(b))
+    }
+
+    // Same as `top_k` under `cmp`; pass a reversed comparison to get the `k` smallest.
+    pub fn top_k_by<F: FnMut(&T, &T) -> Ordering>(&self, k: usize, mut cmp: F) -> Vec<&T> {
+        let mut refs: Vec<&T> = self.iter().collect();
+        let k = k.min(refs.len());
+        let mut desc = |a: &&T, b: &&T| cmp(b, a);
+        if k > 0 && k < refs.len() {
+            refs.select_nth_unstable_by(k - 1, &mut desc);
+        }
+        refs.truncate(k);
+        refs.sort_by(desc);
+        refs
+    }
+}
+
+unsafe fn merge_sort<T, F: FnMut(&T, &T) -> bool>(
+    v: *mut T,
+    len: usize,
+    buf: *mut T,
+    is_less: &mut F,
+) {
+    if len <= INSERTION_CUTOFF {
+        insertion_sort(v, len, is_less);
+        return;
+    }
+    let mid = len / 2;
+    merge_sort(v, mid, buf, is_less);
+    merge_sort(v.add(mid), len - mid, buf, is_less);
+    // Runs that are already in order need no merge
+    if !is_less(&*v.add(mid), &*v.add(mid - 1)) {
+        return;
+    }
+    merge(v, mid, len, buf, is_less);
+}
+
+// Merges the sorted runs `v[..mid]` and `v[mid..len]`. The left run is moved to `buf` and merged
+// forward into `v`; the write position never overtakes the unread part of the right run. On
+// unwind `Hole` moves whatever is left of the left run back into the gap.
+unsafe fn merge<T, F: FnMut(&T, &T) -> bool>(
+    v: *mut T,
+    mid: usize,
+    len: usize,
+    buf: *mut T,
+    is_less: &mut F,
+) {
+    ptr::copy_nonoverlapping(v, buf, mid);
+    let mut hole = Hole {
+        start: buf,
+        end: buf.add(mid),
+        dest: v,
+    };
+    let mut right = v.add(mid);
+    let right_end = v.add(len);
+    while hole.start < hole.end && right < right_end {
+        let take_right = is_less(&*right, &*hole.start);
+        let src = if take_right {
+            let r = right;
+            right = right.add(1);
+            r
+        } else {
+            let l = hole.start;
+            hole.start = hole.start.add(1);
+            l
+        };
+        ptr::copy_nonoverlapping(src, hole.dest, 1);
+        hole.dest = hole.dest.add(1);
+    }
+}
+
+struct Hole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let n = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, n);
+        }
+    }
+}
+
+unsafe fn insertion_sort<T, F: FnMut(&T, &T) -> bool>(v: *mut T, len: usize, is_less: &mut F) {
+    for i in 1..len {
+        if !is_less(&*v.add(i), &*v.add(i - 1)) {
+            continue;
+        }
+        // Lift `v[i]` out and shift larger elements right; on unwind the guard drops it back
+        // into the current gap.
+        let tmp = ManuallyDrop::new(ptr::read(v.add(i)));
+        let mut hole = Hole {
+            start: &*tmp as *const T as *mut T,
+            end: (&*tmp as *const T as *mut T).add(1),
+            dest: v.add(i - 1),
+        };
+        ptr::copy_nonoverlapping(hole.dest, hole.dest.add(1), 1);
+        while hole.dest > v && is_less(&*tmp, &*hole.dest.sub(1)) {
+            ptr::copy_nonoverlapping(hole.dest.sub(1), hole.dest, 1);
+            hole.dest = hole.dest.sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+    use alloc::boxed::Box;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    fn keys(n: usize, seed: u64) -> std::vec::Vec<(u32, usize)> {
+        let mut s = seed;
+        (0..n)
+            .map(|i| {
+                s = s
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((s >> 40) as u32 % 64, i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stable_like_std() {
+        for n in [0, 1, 5, 20, 21, 100, 1000] {
+            let raw = keys(n, n as u64);
+            let mut a: Vec<(u32, Box<usize>)> =
+                raw.iter().map(|&(k, i)| (k, Box::new(i))).collect();
+            let mut want = raw.clone();
+            a.sort_by_key(|x| x.0);
+            want.sort_by_key(|x| x.0);
+            let got: std::vec::Vec<(u32, usize)> = a.iter().map(|(k, b)| (*k, **b)).collect();
+            assert_eq!(got, want);
+
+            let mut b: Vec<u32> = raw.iter().map(|x| x.0).collect();
+            b.sort();
+            assert!(b.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn argsort_parallel_columns() {
+        let names: Vec<&str> = ["d", "b", "a", "c", "b"].iter().copied().collect();
+        let mut ages: Vec<u32> = [40, 20, 30, 10, 50].iter().copied().collect();
+
+        let perm = names.argsort();
+        assert_eq!(&perm[..], &[2, 1, 4, 3, 0]);
+        assert_eq!(&names.gather(&perm)[..], &["a", "b", "b", "c", "d"]);
+        ages.permute_in_place(&perm);
+        assert_eq!(&ages[..], &[30, 20, 50, 10, 40]);
+
+        assert_eq!(
+            &ages.argsort_by_key(|&a| u32::MAX - a)[..],
+            &[2, 4, 0, 1, 3]
+        );
+        assert_eq!(&names.argsort_by(|a, b| b.cmp(a))[..], &[0, 3, 1, 4, 2]);
+        assert!(Vec::<u8>::new().argsort().is_empty());
+    }
+
+    #[test]
+    fn top_k() {
+        let raw = keys(1000, 9);
+        let a: Vec<u32> = raw.iter().map(|x| x.0 * 1000 + x.1 as u32).collect();
+        let mut want: std::vec::Vec<u32> = a.iter().copied().collect();
+        want.sort();
+        want.reverse();
+        for k in [0, 1, 10, 999, 1000, 2000] {
+            let got: std::vec::Vec<u32> = a.top_k(k).into_iter().copied().collect();
+            assert_eq!(got, want[..k.min(1000)]);
+        }
+        let smallest: std::vec::Vec<u32> = a
+            .top_k_by(3, |x, y| y.cmp(x))
+            .into_iter()
+            .copied()
+            .collect();
+        want.reverse();
+        assert_eq!(smallest, want[..3]);
+    }
+
+    #[test]
+    fn panicking_comparator() {
+        for n in [10, 500] {
+            let raw = keys(n, 3);
+            let mut a: Vec<Box<usize>> = raw.iter().map(|&(_, i)| Box::new(i)).collect();
+            let mut calls = 0;
+            let r = catch_unwind(AssertUnwindSafe(|| {
+                a.sort_by(|x, y| {
+                    calls += 1;
+                    if calls == n * 2 {
+                        panic!("comparator");
+                    }
+                    y.cmp(x)
+                })
+            }));
+            assert!(r.is_err());
+            // Every element survives exactly once
+            let mut got: std::vec::Vec<usize> = a.iter().map(|b| **b).collect();
+            got.sort();
+            assert_eq!(got, (0..n).collect::<std::vec::Vec<_>>());
+        }
+    }
+}
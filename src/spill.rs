@@ -0,0 +1,246 @@
+use crate::{MemoryReport, Vec};
+use bytemuck::Pod;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+// An append-only vector that keeps at most `window` elements in memory. When the window fills
+// up its raw bytes are appended to a temporary file and the window starts over, so memory stays
+// bounded while iteration still sees every element in push order. The file is created on the
+// first spill and removed on drop.
+pub struct SpillVec<T: Pod> {
+    window: Vec<T>,
+    limit: usize,
+    dir: PathBuf,
+    file: Option<(File, PathBuf)>,
+    spilled: usize,
+}
+
+impl<T: Pod> SpillVec<T> {
+    pub fn new(window: usize) -> Self {
+        Self::in_dir(std::env::temp_dir(), window)
+    }
+
+    pub fn in_dir<P: AsRef<Path>>(dir: P, window: usize) -> Self {
+        assert!(window != 0, "window must hold at least one element");
+        assert!(
+            core::mem::size_of::<T>() != 0,
+            "zero-sized elements can't be spilled"
+        );
+        Self {
+            window: Vec::new(),
+            limit: window,
+            dir: dir.as_ref().to_path_buf(),
+            file: None,
+            spilled: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.spilled + self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Number of elements currently on disk.
+    pub fn spilled_len(&self) -> usize {
+        self.spilled
+    }
+
+    // If the window is full and can't be spilled, `elem` is handed back with the error and the
+    // vector is left as it was, so the push can be retried.
+    pub fn push(&mut self, elem: T) -> Result<(), (io::Error, T)> {
+        if self.window.len() == self.limit {
+            if let Err(e) = self.spill() {
+                return Err((e, elem));
+            }
+        }
+        self.window.push(elem);
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.file.is_none() {
+            let name = std::format!(
+                "rust_vec-spill-{}-{}",
+                std::process::id(),
+                SPILL_FILES.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = self.dir.join(name);
+            let file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            self.file = Some((file, path));
+        }
+        let (file, _) = self.file.as_mut().unwrap();
+        // Writes always start at the end of the spilled elements, so a partial write from an
+        // earlier failure is overwritten rather than read back as elements.
+        let end = (self.spilled * core::mem::size_of::<T>()) as u64;
+        if let Err(e) = write_at(file, end, bytemuck::cast_slice(&self.window)) {
+            // Best effort: only frees the disk space, correctness doesn't depend on it
+            let _ = file.set_len(end);
+            return Err(e);
+        }
+        self.spilled += self.window.len();
+        self.window.clear();
+        Ok(())
+    }
+
+    // Reads the spilled part back through its own file handle, `window` elements at a time,
+    // then continues with the in-memory window.
+    pub fn iter(&self) -> io::Result<SpillIter<'_, T>> {
+        let file = match &self.file {
+            Some((_, path)) => Some(File::open(path)?),
+            None => None,
+        };
+        Ok(SpillIter {
+            vec: self,
+            file,
+            buf: Vec::new(),
+            pos: 0,
+            read: 0,
+        })
+    }
+}
+
+fn write_at(file: &mut File, offset: u64, bytes: &[u8]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)
+}
+
+impl<T: Pod> Drop for SpillVec<T> {
+    fn drop(&mut self) {
+        if let Some((file, path)) = self.file.take() {
+            drop(file);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub struct SpillIter<'a, T: Pod> {
+    vec: &'a SpillVec<T>,
+    file: Option<File>,
+    buf: Vec<T>,
+    // Position in `buf`, and number of elements read from disk so far
+    pos: usize,
+    read: usize,
+}
+
+impl<T: Pod> Iterator for SpillIter<'_, T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.pos == self.buf.len() && self.read < self.vec.spilled {
+            let n = self.vec.limit.min(self.vec.spilled - self.read);
+            self.buf.clear();
+            for _ in 0..n {
+                self.buf.push(T::zeroed());
+            }
+            let file = self.file.as_mut().unwrap();
+            if let Err(e) = file.read_exact(bytemuck::cast_slice_mut(&mut self.buf)) {
+                // Give up on the disk part rather than yield the same error forever
+                self.read = self.vec.spilled;
+                self.buf.clear();
+                self.pos = 0;
+                return Some(Err(e));
+            }
+            self.read += n;
+            self.pos = 0;
+        }
+        if self.pos < self.buf.len() {
+            self.pos += 1;
+            return Some(Ok(self.buf[self.pos - 1]));
+        }
+        let i = self.pos - self.buf.len();
+        let elem = *self.vec.window.get(i)?;
+        self.pos += 1;
+        Some(Ok(elem))
+    }
+}
+
+impl<T: Pod> MemoryReport for SpillVec<T> {
+    // Only the in-memory window; spilled elements live in the file.
+    fn allocated_bytes(&self) -> usize {
+        self.window.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.window.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spills_and_iterates() {
+        let mut v = SpillVec::<u64>::new(100);
+        for i in 0..1050 {
+            v.push(i * 3).unwrap();
+        }
+        assert_eq!(v.len(), 1050);
+        assert_eq!(v.spilled_len(), 1000);
+        let path = v.file.as_ref().unwrap().1.clone();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 8000);
+
+        let got: io::Result<std::vec::Vec<u64>> = v.iter().unwrap().collect();
+        assert_eq!(
+            got.unwrap(),
+            (0..1050).map(|i| i * 3).collect::<std::vec::Vec<_>>()
+        );
+        drop(v);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn stays_in_memory() {
+        let mut v = SpillVec::<[u8; 3]>::new(4);
+        assert_eq!(v.iter().unwrap().count(), 0);
+        for i in 0..4 {
+            v.push([i; 3]).unwrap();
+        }
+        assert!(v.file.is_none());
+        let got: std::vec::Vec<[u8; 3]> = v.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(got, [[0; 3], [1; 3], [2; 3], [3; 3]]);
+    }
+
+    #[test]
+    fn failed_spill_returns_the_element() {
+        let mut v = SpillVec::<u32>::in_dir("/nonexistent/rust_vec", 2);
+        v.push(1).unwrap();
+        v.push(2).unwrap();
+        let (_, elem) = v.push(3).unwrap_err();
+        assert_eq!(elem, 3);
+        assert_eq!(v.len(), 2);
+        assert!(v.file.is_none());
+    }
+
+    #[test]
+    fn failed_write_keeps_the_file_consistent() {
+        let mut v = SpillVec::<u32>::new(2);
+        for i in 0..5 {
+            v.push(i).unwrap();
+        }
+        let path = v.file.as_ref().unwrap().1.clone();
+        // A read-only handle makes the next write fail
+        let writable =
+            std::mem::replace(&mut v.file.as_mut().unwrap().0, File::open(&path).unwrap());
+        v.push(5).unwrap();
+        let (_, elem) = v.push(6).unwrap_err();
+        assert_eq!(elem, 6);
+        assert_eq!(v.spilled_len(), 4);
+
+        v.file.as_mut().unwrap().0 = writable;
+        v.push(6).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 6 * 4);
+        let got: io::Result<std::vec::Vec<u32>> = v.iter().unwrap().collect();
+        assert_eq!(got.unwrap(), [0, 1, 2, 3, 4, 5, 6]);
+    }
+}
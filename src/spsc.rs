@@ -0,0 +1,191 @@
+// A bounded single-producer single-consumer queue. `head` and `tail` count pops and pushes
+// since creation (wrapping), so `tail - head` is the number of queued items and slot
+// `i % capacity` holds item `i`. Each side only stores its own counter: the producer publishes a
+// written slot by releasing `tail`, and the consumer hands a slot back by releasing `head`.
+use crate::RawVec;
+use core::ptr;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+#[cfg(not(loom))]
+use alloc::sync::Arc;
+#[cfg(not(loom))]
+use core::sync::atomic::AtomicUsize;
+#[cfg(loom)]
+use loom::sync::atomic::AtomicUsize;
+#[cfg(loom)]
+use loom::sync::Arc;
+
+struct Shared<T> {
+    buf: RawVec<T>,
+    capacity: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Slots are only touched by the side that currently owns them, as decided by `head`/`tail`.
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    fn slot(&self, i: usize) -> *mut T {
+        unsafe { self.buf.ptr.as_ptr().add(i % self.capacity) }
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let (head, tail) = (self.head.load(Relaxed), self.tail.load(Relaxed));
+        let mut i = head;
+        while i != tail {
+            unsafe { ptr::drop_in_place(self.slot(i)) };
+            i = i.wrapping_add(1);
+        }
+    }
+}
+
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub fn queue<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let shared = Arc::new(Shared {
+        buf: RawVec::with_capacity(capacity),
+        capacity,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (
+        Producer {
+            shared: shared.clone(),
+        },
+        Consumer { shared },
+    )
+}
+
+impl<T> Producer<T> {
+    // Hands `elem` back if the queue is full.
+    pub fn push(&mut self, elem: T) -> Result<(), T> {
+        let s = &*self.shared;
+        let tail = s.tail.load(Relaxed);
+        // Pairs with the consumer's release of `head`: the slot has been read out
+        if tail.wrapping_sub(s.head.load(Acquire)) == s.capacity {
+            return Err(elem);
+        }
+        unsafe { ptr::write(s.slot(tail), elem) };
+        s.tail.store(tail.wrapping_add(1), Release);
+        Ok(())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn pop(&mut self) -> Option<T> {
+        let s = &*self.shared;
+        let head = s.head.load(Relaxed);
+        // Pairs with the producer's release of `tail`: the slot has been written
+        if head == s.tail.load(Acquire) {
+            return None;
+        }
+        let elem = unsafe { ptr::read(s.slot(head)) };
+        s.head.store(head.wrapping_add(1), Release);
+        Some(elem)
+    }
+
+    pub fn len(&self) -> usize {
+        let s = &*self.shared;
+        s.tail.load(Acquire).wrapping_sub(s.head.load(Relaxed))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use alloc::rc::Rc;
+
+    #[test]
+    fn across_threads() {
+        let (mut p, mut c) = queue::<Box<usize>>(8);
+        let producer = std::thread::spawn(move || {
+            for i in 0..10_000 {
+                let mut item = Box::new(i);
+                while let Err(back) = p.push(item) {
+                    item = back;
+                    std::thread::yield_now();
+                }
+            }
+        });
+        let mut next = 0;
+        while next < 10_000 {
+            match c.pop() {
+                Some(x) => {
+                    assert_eq!(*x, next);
+                    next += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        producer.join().unwrap();
+        assert!(c.pop().is_none());
+    }
+
+    #[test]
+    fn full_and_drop() {
+        let rc = Rc::new(());
+        let (mut p, mut c) = queue(2);
+        p.push(rc.clone()).unwrap();
+        p.push(rc.clone()).unwrap();
+        assert!(p.push(rc.clone()).is_err());
+        assert_eq!(c.len(), 2);
+        drop(c.pop());
+        p.push(rc.clone()).unwrap();
+        drop((p, c));
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let (mut p, mut c) = queue(0);
+        assert_eq!(p.push(1), Err(1));
+        assert_eq!(c.pop(), None);
+    }
+}
+
+// Run with `RUSTFLAGS="--cfg loom" cargo test --release --lib spsc`.
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn fifo_through_wraparound() {
+        loom::model(|| {
+            let (mut p, mut c) = queue::<usize>(2);
+            let producer = thread::spawn(move || {
+                for i in 0..3 {
+                    while p.push(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut next = 0;
+            while next < 3 {
+                match c.pop() {
+                    Some(x) => {
+                        assert_eq!(x, next);
+                        next += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+            producer.join().unwrap();
+        });
+    }
+}
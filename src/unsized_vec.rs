@@ -0,0 +1,240 @@
+use crate::{MemoryReport, Vec};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use core::marker::{PhantomData, Unsize};
+use core::ops::{Index, IndexMut};
+use core::ptr::{self, NonNull, Pointee};
+
+// Unsized values (`dyn Trait`, `str`) stored inline in one byte buffer. Each value sits at an
+// offset aligned for its concrete type; `items` keeps that offset next to the pointer metadata
+// (vtable or length) needed to rebuild a `&T`. The buffer is aligned to the largest alignment
+// pushed so far, so growing it re-copies the bytes into a more aligned block when needed.
+pub struct UnsizedVec<T: ?Sized> {
+    ptr: NonNull<u8>,
+    cap: usize,
+    align: usize,
+    used: usize,
+    items: Vec<(usize, <T as Pointee>::Metadata)>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: ?Sized> UnsizedVec<T> {
+    pub fn new() -> Self {
+        Self {
+            ptr: NonNull::<u8>::dangling(),
+            cap: 0,
+            align: 1,
+            used: 0,
+            items: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Bytes used by the values themselves, including alignment padding.
+    pub fn byte_len(&self) -> usize {
+        self.used
+    }
+
+    pub fn push<U: Unsize<T>>(&mut self, value: U) {
+        let meta = ptr::metadata(&value as &T);
+        let offset = self.reserve_for(Layout::new::<U>());
+        unsafe { ptr::write(self.ptr.as_ptr().add(offset) as *mut U, value) };
+        self.items.push((offset, meta));
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let &(offset, meta) = self.items.get(index)?;
+        Some(unsafe { &*ptr::from_raw_parts(self.ptr.as_ptr().add(offset), meta) })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let &(offset, meta) = self.items.get(index)?;
+        Some(unsafe { &mut *ptr::from_raw_parts_mut(self.ptr.as_ptr().add(offset), meta) })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len()).map(move |i| &self[i])
+    }
+
+    pub fn clear(&mut self) {
+        let items = core::mem::take(&mut self.items);
+        self.used = 0;
+        for &(offset, meta) in items.iter() {
+            unsafe {
+                ptr::drop_in_place(ptr::from_raw_parts_mut::<T>(
+                    self.ptr.as_ptr().add(offset),
+                    meta,
+                ))
+            };
+        }
+    }
+
+    // Returns the offset where a value with `layout` can be written, growing the buffer first if
+    // it is too small or not aligned enough.
+    fn reserve_for(&mut self, layout: Layout) -> usize {
+        let offset = self
+            .used
+            .checked_next_multiple_of(layout.align())
+            .expect("capacity overflow");
+        let end = offset
+            .checked_add(layout.size())
+            .expect("capacity overflow");
+        if end > self.cap || layout.align() > self.align {
+            let align = self.align.max(layout.align());
+            let cap = end.max(self.cap * 2).max(64);
+            let new_layout = Layout::from_size_align(cap, align).expect("capacity overflow");
+            let new_ptr = unsafe { alloc(new_layout) };
+            let new_ptr = NonNull::new(new_ptr).unwrap_or_else(|| handle_alloc_error(new_layout));
+            unsafe {
+                ptr::copy_nonoverlapping(self.ptr.as_ptr(), new_ptr.as_ptr(), self.used);
+                self.free();
+            }
+            self.ptr = new_ptr;
+            self.cap = cap;
+            self.align = align;
+        }
+        self.used = end;
+        offset
+    }
+
+    unsafe fn free(&mut self) {
+        if self.cap != 0 {
+            let layout = Layout::from_size_align_unchecked(self.cap, self.align);
+            dealloc(self.ptr.as_ptr(), layout);
+        }
+    }
+}
+
+impl UnsizedVec<str> {
+    pub fn push_str(&mut self, s: &str) {
+        let offset = self.reserve_for(Layout::for_value(s));
+        unsafe { ptr::copy_nonoverlapping(s.as_ptr(), self.ptr.as_ptr().add(offset), s.len()) };
+        self.items.push((offset, s.len()));
+    }
+}
+
+impl<T: ?Sized> Drop for UnsizedVec<T> {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe { self.free() };
+    }
+}
+
+impl<T: ?Sized> Default for UnsizedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ?Sized> Index<usize> for UnsizedVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: ?Sized> IndexMut<usize> for UnsizedVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+impl<T: ?Sized> MemoryReport for UnsizedVec<T> {
+    // The value bytes, padding included, plus the offset and metadata of each item.
+    fn allocated_bytes(&self) -> usize {
+        self.cap + self.items.allocated_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.used + self.items.used_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use alloc::string::ToString;
+    use core::fmt::Display;
+
+    struct Empty;
+
+    impl Display for Empty {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("empty")
+        }
+    }
+
+    #[test]
+    fn mixed_types() {
+        let mut v: UnsizedVec<dyn Display> = UnsizedVec::new();
+        v.push(1u8);
+        v.push(2u128);
+        v.push("three");
+        v.push(Empty);
+        v.push([4u8; 3].len());
+        for i in 0..100u64 {
+            v.push(i);
+        }
+        assert_eq!(v.len(), 105);
+        assert_eq!(v[1].to_string(), "2");
+        assert_eq!(v[2].to_string(), "three");
+        assert_eq!(v[3].to_string(), "empty");
+        assert_eq!(v[4].to_string(), "3");
+        assert_eq!(v[104].to_string(), "99");
+        assert!(v.get(105).is_none());
+        for i in 0..v.len() {
+            let p = &v[i] as *const dyn Display as *const u8 as usize;
+            assert_eq!(p % core::mem::align_of_val(&v[i]), 0);
+        }
+    }
+
+    #[test]
+    fn mutate_and_drop() {
+        trait Counter {
+            fn bump(&mut self) -> usize;
+        }
+        impl Counter for (Rc<()>, usize) {
+            fn bump(&mut self) -> usize {
+                self.1 += 1;
+                self.1
+            }
+        }
+
+        let rc = Rc::new(());
+        let mut v: UnsizedVec<dyn Counter> = UnsizedVec::new();
+        for i in 0..10 {
+            v.push((rc.clone(), i));
+        }
+        assert_eq!(v[3].bump(), 4);
+        assert_eq!(Rc::strong_count(&rc), 11);
+        v.clear();
+        assert_eq!(Rc::strong_count(&rc), 1);
+        v.push((rc.clone(), 0));
+        drop(v);
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn strings() {
+        let mut v: UnsizedVec<str> = UnsizedVec::default();
+        v.push_str("hello");
+        v.push_str("");
+        v.push_str("world");
+        let all: std::vec::Vec<&str> = v.iter().collect();
+        assert_eq!(all, ["hello", "", "world"]);
+        assert_eq!(v.byte_len(), 10);
+        assert_eq!(
+            v.used_bytes(),
+            10 + 3 * core::mem::size_of::<(usize, <str as Pointee>::Metadata)>()
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use crate::Vec;
+#[cfg(not(feature = "no_global_oom_handling"))]
+use alloc::borrow::Cow;
+#[cfg(not(feature = "no_global_oom_handling"))]
+use alloc::string::String;
+use core::alloc::Allocator;
+use core::str::Utf8Error;
+
+// Text views of a byte buffer that leave it a `Vec<u8>`, e.g. to look at a network buffer as
+// text without moving it into a `String`.
+impl<A: Allocator> Vec<u8, A> {
+    pub fn as_str_checked(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(self)
+    }
+
+    // Borrows when the bytes are valid; otherwise copies them with each invalid sequence
+    // replaced by U+FFFD.
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn as_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+
+    #[cfg(not(feature = "no_global_oom_handling"))]
+    pub fn push_utf8(&mut self, c: char) {
+        self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+    use alloc::borrow::Cow;
+
+    #[test]
+    fn utf8_views() {
+        let mut v = Vec::new();
+        v.extend_from_slice(b"caf");
+        v.push_utf8('é');
+        v.push_utf8('🦀');
+        assert_eq!(v.len(), 9);
+        assert_eq!(v.as_str_checked(), Ok("café🦀"));
+        assert!(matches!(v.as_str_lossy(), Cow::Borrowed("café🦀")));
+
+        v.push(0xff);
+        assert_eq!(v.as_str_checked().unwrap_err().valid_up_to(), 9);
+        assert_eq!(v.as_str_lossy(), "café🦀\u{fffd}");
+    }
+}
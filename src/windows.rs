@@ -0,0 +1,65 @@
+use crate::Vec;
+use core::alloc::Allocator;
+
+// Overlapping mutable windows of `size` elements, one step apart. Each window borrows the
+// iterator, so only one is alive at a time; that is what makes handing out `&mut` to
+// overlapping ranges sound, and why this has an inherent `next` instead of implementing
+// `Iterator`:
+//
+//     let mut w = v.windows_mut(3);
+//     while let Some([a, b, c]) = w.next() {
+//         *b = (*a + *b + *c) / 3;
+//     }
+pub struct WindowsMut<'a, T> {
+    elems: &'a mut [T],
+    size: usize,
+    pos: usize,
+}
+
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn windows_mut(&mut self, size: usize) -> WindowsMut<'_, T> {
+        assert!(size != 0, "window size must be non-zero");
+        WindowsMut {
+            elems: self,
+            size,
+            pos: 0,
+        }
+    }
+}
+
+impl<T> WindowsMut<'_, T> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut [T]> {
+        let end = self.pos.checked_add(self.size)?;
+        let window = self.elems.get_mut(self.pos..end)?;
+        self.pos += 1;
+        Some(window)
+    }
+
+    pub fn remaining(&self) -> usize {
+        (self.elems.len() + 1).saturating_sub(self.pos + self.size)
+    }
+}
+
+#[cfg(all(test, not(feature = "no_global_oom_handling")))]
+mod tests {
+    use crate::Vec;
+
+    #[test]
+    fn windows_mut() {
+        let mut v: Vec<i32> = (0..6).collect();
+        let mut w = v.windows_mut(3);
+        assert_eq!(w.remaining(), 4);
+        // Running sums: each window sees the write made through the previous one
+        while let Some([a, b, _]) = w.next() {
+            *b += *a;
+        }
+        assert_eq!(w.remaining(), 0);
+        assert_eq!(v, [0, 1, 3, 6, 10, 5]);
+
+        assert!(v.windows_mut(7).next().is_none());
+        let mut w = v.windows_mut(6);
+        assert_eq!(w.next().map(|w| w.len()), Some(6));
+        assert!(w.next().is_none());
+    }
+}
@@ -0,0 +1,12 @@
+// Pins down what must not compile: auto traits that follow the element type, the dropck rules
+// of `Vec`, and the invariance the `shadow` feature imposes. Refresh the expected output with
+// `TRYBUILD=overwrite` after a toolchain bump.
+// The fixtures use `push`, which doesn't exist without global OOM handling.
+#![cfg(not(feature = "no_global_oom_handling"))]
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "shadow")]
+    t.compile_fail("tests/ui/shadow/*.rs");
+}
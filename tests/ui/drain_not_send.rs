@@ -0,0 +1,7 @@
+use std::rc::Rc;
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    assert_send::<rust_vec::Drain<'static, Rc<u8>>>();
+}
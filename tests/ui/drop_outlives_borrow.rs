@@ -0,0 +1,6 @@
+// `Vec`'s destructor may look at its elements, so they have to outlive the vector.
+fn main() {
+    let mut v = rust_vec::Vec::new();
+    let s = String::from("borrowed");
+    v.push(&s);
+}
@@ -0,0 +1,7 @@
+use std::cell::Cell;
+
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    assert_sync::<rust_vec::IntoIter<Cell<u8>>>();
+}
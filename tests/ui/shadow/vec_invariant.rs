@@ -0,0 +1,7 @@
+fn shorten<'a>(v: rust_vec::Vec<&'static str>) -> rust_vec::Vec<&'a str> {
+    v
+}
+
+fn main() {
+    shorten(rust_vec::Vec::new());
+}
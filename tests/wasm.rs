@@ -0,0 +1,46 @@
+// Smoke tests for wasm32, where `usize` is 32 bits and a panic aborts the whole run. Run with
+// `wasm-bindgen-test-runner` as the target runner; see the `wasm` CI job.
+#![cfg(all(target_arch = "wasm32", not(feature = "no_global_oom_handling")))]
+use rust_vec::{TryReserveError, Vec};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn push_and_remove() {
+    let mut v = Vec::new();
+    for i in 0..1000u64 {
+        v.push(i);
+    }
+    v.retain_unordered(|&x| x % 3 == 0);
+    assert_eq!(v.len(), 334);
+    v.sort();
+    assert_eq!(v.remove(1), 3);
+    v.truncate(2);
+    assert_eq!(v, [0, 6]);
+}
+
+#[wasm_bindgen_test]
+fn capacity_fits_address_space() {
+    let mut v = Vec::<u64>::new();
+    // `isize::MAX` bytes is 2 GiB here
+    assert_eq!(
+        v.try_reserve(usize::MAX / 8),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(
+        v.try_reserve(isize::MAX as usize / 8 + 1),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    assert_eq!(v.try_reserve(16), Ok(()));
+}
+
+#[wasm_bindgen_test]
+fn max_capacity() {
+    let mut v = Vec::<u8>::new().with_max_capacity(1 << 20);
+    assert_eq!(
+        v.try_reserve((1 << 20) + 1),
+        Err(TryReserveError::CapacityOverflow)
+    );
+    v.extend(core::iter::repeat_n(7, 1 << 20));
+    assert_eq!(v.capacity(), 1 << 20);
+    assert_eq!(v.try_push(8), Err((TryReserveError::CapacityOverflow, 8)));
+}